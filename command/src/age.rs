@@ -0,0 +1,62 @@
+use std::time::{Duration, SystemTime};
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+const MONTH: u64 = 30 * DAY;
+const YEAR: u64 = 365 * DAY;
+
+/// How long ago `time` was, rounded down to the largest whole unit.
+pub fn format_age(time: SystemTime) -> String {
+    let seconds = SystemTime::now()
+        .duration_since(time)
+        .unwrap_or_else(|_| Duration::from_secs(0))
+        .as_secs();
+
+    let (count, unit) = match seconds {
+        s if s >= YEAR => (s / YEAR, "year"),
+        s if s >= MONTH => (s / MONTH, "month"),
+        s if s >= WEEK => (s / WEEK, "week"),
+        s if s >= DAY => (s / DAY, "day"),
+        s if s >= HOUR => (s / HOUR, "hour"),
+        s if s >= MINUTE => (s / MINUTE, "minute"),
+        _ => return "just now".into(),
+    };
+
+    if count == 1 {
+        format!("1 {} ago", unit)
+    } else {
+        format!("{} {}s ago", count, unit)
+    }
+}
+
+/// Parse an age like `30d`, `2w` or `6months`.
+pub fn parse_age(age: &str) -> Result<Duration, String> {
+    let age = age.trim();
+    let split = age.find(|c: char| !c.is_ascii_digit()).unwrap_or(age.len());
+    let (count, unit) = age.split_at(split);
+
+    let count = count
+        .parse::<u64>()
+        .map_err(|_| format!("'{}' does not start with a number", age))?;
+
+    let unit = match unit.trim() {
+        "h" | "hour" | "hours" => HOUR,
+        "d" | "day" | "days" => DAY,
+        "w" | "week" | "weeks" => WEEK,
+        "mo" | "month" | "months" => MONTH,
+        "y" | "year" | "years" => YEAR,
+        unit => {
+            return Err(format!(
+                "Unknown unit '{}', expected one of h, d, w, mo or y",
+                unit
+            ))
+        }
+    };
+
+    count
+        .checked_mul(unit)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("'{}' is too long", age))
+}
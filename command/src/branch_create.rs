@@ -0,0 +1,112 @@
+use crate::{Command, CommandBoxClone, WorkOption, WorkResult};
+use color_printer::{Color, ColorPrinter, ColorSpec};
+use command_derive::CommandBoxClone;
+use gitlib::{self, GitRepo};
+use std::{io::Write, path::PathBuf};
+
+#[derive(Clone, CommandBoxClone)]
+pub struct BranchCreateCommand {
+    branch: String,
+    base: String,
+    checkout: bool,
+    track: bool,
+}
+
+impl BranchCreateCommand {
+    pub fn new(branch: String, base: String, checkout: bool, track: bool) -> Self {
+        Self {
+            branch,
+            base,
+            checkout,
+            track,
+        }
+    }
+}
+
+struct BranchCreateCommandResult {
+    path: PathBuf,
+    branch: String,
+    created: bool,
+    upstream: Option<String>,
+    checked_out: bool,
+    error: Option<gitlib::Error>,
+}
+
+impl Command for BranchCreateCommand {
+    fn process(&self, repo: GitRepo) -> WorkOption {
+        let mut result = BranchCreateCommandResult {
+            path: repo.path().into(),
+            branch: self.branch.clone(),
+            created: false,
+            upstream: None,
+            checked_out: false,
+            error: None,
+        };
+
+        // Only a remote branch can be tracked, so check before creating anything
+        if self.track {
+            if let Err(e) = repo.has_remote_branch(&self.base) {
+                result.error = Some(e);
+                return Some(Box::new(result));
+            }
+        }
+
+        match repo.create_branch(&self.branch, &self.base) {
+            Ok(()) => result.created = true,
+            Err(gitlib::Error::Exists) => {}
+            Err(e) => {
+                result.error = Some(e);
+                return Some(Box::new(result));
+            }
+        }
+
+        if result.created && self.track {
+            match repo.set_upstream(&self.branch, &self.base) {
+                Ok(()) => result.upstream = Some(self.base.clone()),
+                Err(e) => result.error = Some(e),
+            }
+        }
+
+        if self.checkout && result.error.is_none() {
+            match repo.checkout(&self.branch) {
+                Ok(checked_out) => result.checked_out = checked_out,
+                Err(e) => result.error = Some(e),
+            }
+        }
+
+        Some(Box::new(result))
+    }
+}
+
+impl WorkResult for BranchCreateCommandResult {
+    fn print(&self, printer: &mut ColorPrinter<'_>) {
+        let mut cs = ColorSpec::new();
+        cs.set_intense(true);
+
+        let (status, color) = match (&self.error, self.created) {
+            (Some(_), _) => ("ERROR", Color::Red),
+            (None, true) => ("created", Color::Green),
+            (None, false) => ("exists", Color::Yellow),
+        };
+
+        cs.set_fg(Some(color));
+
+        printer.color_context(&cs, |h| {
+            write!(h, " {} - {}", self.branch, status).expect("write fail");
+
+            if let Some(ref e) = self.error {
+                write!(h, " {:?}", e).expect("write fail");
+            }
+        });
+
+        if let Some(ref upstream) = self.upstream {
+            write!(printer, " - tracking {}", upstream).expect("write fail");
+        }
+
+        if self.checked_out {
+            write!(printer, " - checked out").expect("write fail");
+        }
+
+        writeln!(printer, " - {}", self.path.display()).expect("write fail");
+    }
+}
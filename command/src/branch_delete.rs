@@ -1,60 +1,259 @@
-use crate::{Command, CommandBoxClone, WorkOption, WorkResult};
+use crate::{BranchPattern, Command, CommandBoxClone, WorkOption, WorkResult};
 use color_printer::{Color, ColorPrinter, ColorSpec};
 use command_derive::CommandBoxClone;
-use gitlib::{self, GitRepo};
+use gitlib::{self, Branch, GitRepo};
 use std::{io::Write, path::PathBuf};
 
+const REMOTE: &str = "origin";
+
 #[derive(Clone, CommandBoxClone)]
 pub struct BranchDeleteCommand {
-    branch: String,
+    pattern: BranchPattern,
+    force: bool,
+    remote: bool,
 }
 
 impl BranchDeleteCommand {
-    pub fn new(branch: String) -> Self {
-        Self { branch }
+    pub fn new(pattern: BranchPattern, force: bool, remote: bool) -> Self {
+        Self {
+            pattern,
+            force,
+            remote,
+        }
+    }
+
+    /// Decide what happens to every local branch matching the pattern.
+    fn verdicts(&self, repo: &GitRepo) -> Result<Vec<(String, Verdict)>, gitlib::Error> {
+        let default_branch = repo.default_branch_name().ok();
+        let mut verdicts = Vec::new();
+
+        for branch in repo.branches(false)? {
+            if !self.pattern.matches(branch.name()) {
+                continue;
+            }
+
+            let verdict = verdict(
+                repo,
+                &branch,
+                default_branch.as_deref(),
+                self.pattern.is_exact(),
+                self.force,
+            )?;
+
+            verdicts.push((branch.name().into(), verdict));
+        }
+
+        Ok(verdicts)
     }
+
+    fn delete(&self, repo: &GitRepo, branch: &mut DeletedBranch) {
+        if let Err(e) = repo.delete_local_branch(&branch.name) {
+            branch.error = Some(e);
+            return;
+        }
+
+        branch.deleted_local = true;
+
+        if !self.remote {
+            return;
+        }
+
+        match repo.delete_remote_branch(REMOTE, &branch.name) {
+            Ok(()) => branch.deleted_remote = true,
+            Err(gitlib::Error::NotFound) => {}
+            Err(e) => branch.error = Some(e),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Verdict {
+    Delete,
+    Unmerged,
+    Protected,
+}
+
+/// The checked out and default branches are never swept up by a pattern,
+/// and unmerged branches are only deleted when forced.
+pub(crate) fn verdict(
+    repo: &GitRepo,
+    branch: &Branch,
+    default_branch: Option<&str>,
+    is_exact: bool,
+    force: bool,
+) -> Result<Verdict, gitlib::Error> {
+    let is_protected = branch.is_head() || default_branch == Some(branch.name());
+
+    if is_protected && !is_exact {
+        Ok(Verdict::Protected)
+    } else if force || repo.is_branch_merged(branch.name())? {
+        Ok(Verdict::Delete)
+    } else {
+        Ok(Verdict::Unmerged)
+    }
+}
+
+struct DeletedBranch {
+    name: String,
+    verdict: Verdict,
+    deleted_local: bool,
+    deleted_remote: bool,
+    error: Option<gitlib::Error>,
 }
 
 struct BranchDeleteCommandResult {
     path: PathBuf,
-    branch: String,
+    branches: Vec<DeletedBranch>,
     error: Option<gitlib::Error>,
 }
 
+struct BranchDeletePlanResult {
+    path: PathBuf,
+    branches: Vec<(String, Verdict)>,
+    remote: bool,
+}
+
 impl Command for BranchDeleteCommand {
     fn process(&self, repo: GitRepo) -> WorkOption {
-        let result = match repo.delete_local_branch(&self.branch) {
-            Ok(()) => BranchDeleteCommandResult {
-                path: repo.path().into(),
-                branch: self.branch.clone(),
-                error: None,
-            },
-            Err(gitlib::Error::NotFound) => return None,
-            Err(e) => BranchDeleteCommandResult {
-                path: repo.path().into(),
-                branch: self.branch.clone(),
-                error: Some(e),
-            },
+        let verdicts = match self.verdicts(&repo) {
+            Ok(ref v) if v.is_empty() => return None,
+            Ok(v) => v,
+            Err(e) => {
+                return Some(Box::new(BranchDeleteCommandResult {
+                    path: repo.path().into(),
+                    branches: Vec::new(),
+                    error: Some(e),
+                }))
+            }
+        };
+
+        let branches = verdicts
+            .into_iter()
+            .map(|(name, verdict)| {
+                let mut branch = DeletedBranch {
+                    name,
+                    verdict,
+                    deleted_local: false,
+                    deleted_remote: false,
+                    error: None,
+                };
+
+                if verdict == Verdict::Delete {
+                    self.delete(&repo, &mut branch);
+                }
+
+                branch
+            })
+            .collect();
+
+        let result = BranchDeleteCommandResult {
+            path: repo.path().into(),
+            branches,
+            error: None,
+        };
+
+        Some(Box::new(result))
+    }
+
+    fn plan(&self, repo: &GitRepo) -> WorkOption {
+        let branches = self.verdicts(repo).ok()?;
+
+        if branches.is_empty() {
+            return None;
+        }
+
+        let result = BranchDeletePlanResult {
+            path: repo.path().into(),
+            branches,
+            remote: self.remote,
         };
 
         Some(Box::new(result))
     }
 }
 
+fn print_skipped(printer: &mut ColorPrinter<'_>, cs: &mut ColorSpec, name: &str, verdict: Verdict) {
+    let reason = match verdict {
+        Verdict::Unmerged => "NOT MERGED, use --force",
+        Verdict::Protected => "PROTECTED",
+        Verdict::Delete => return,
+    };
+
+    cs.set_fg(Some(Color::Yellow));
+
+    printer.color_context(cs, |h| {
+        write!(h, " {} - {}", name, reason).expect("write fail")
+    });
+}
+
 impl WorkResult for BranchDeleteCommandResult {
     fn print(&self, printer: &mut ColorPrinter<'_>) {
+        writeln!(printer, "{}", self.path.display()).expect("write fail");
+
         let mut cs = ColorSpec::new();
         cs.set_intense(true);
-        cs.set_fg(Some(Color::Red));
 
-        printer.color_context(&cs, |h| {
-            write!(h, " {}", self.branch).expect("write fail");
+        if let Some(ref e) = self.error {
+            cs.set_fg(Some(Color::Red));
+            printer.color_context(&cs, |h| write!(h, " ERROR {:?}", e).expect("write fail"));
+            writeln!(printer).expect("write fail");
+        }
+
+        for branch in &self.branches {
+            if branch.verdict != Verdict::Delete {
+                print_skipped(printer, &mut cs, &branch.name, branch.verdict);
+                writeln!(printer).expect("write fail");
+                continue;
+            }
+
+            cs.set_fg(Some(Color::Red));
 
-            if self.error.is_some() {
-                write!(h, " - ERROR").expect("write fail");
+            printer.color_context(&cs, |h| {
+                write!(h, " {}", branch.name).expect("write fail");
+
+                if branch.deleted_local {
+                    write!(h, " - deleted").expect("write fail");
+                }
+
+                if branch.deleted_remote {
+                    write!(h, " - deleted on {}", REMOTE).expect("write fail");
+                }
+
+                if let Some(ref e) = branch.error {
+                    write!(h, " - ERROR {:?}", e).expect("write fail");
+                }
+            });
+
+            writeln!(printer).expect("write fail");
+        }
+    }
+}
+
+impl WorkResult for BranchDeletePlanResult {
+    fn print(&self, printer: &mut ColorPrinter<'_>) {
+        writeln!(printer, "{}", self.path.display()).expect("write fail");
+
+        let mut cs = ColorSpec::new();
+        cs.set_intense(true);
+
+        for (name, verdict) in &self.branches {
+            if *verdict != Verdict::Delete {
+                print_skipped(printer, &mut cs, name, *verdict);
+                writeln!(printer).expect("write fail");
+                continue;
             }
-        });
 
-        writeln!(printer, " - {}", self.path.display()).expect("write fail");
+            cs.set_fg(Some(Color::Red));
+
+            printer.color_context(&cs, |h| {
+                write!(h, " Delete {}", name).expect("write fail");
+
+                if self.remote {
+                    write!(h, " and {}/{}", REMOTE, name).expect("write fail");
+                }
+            });
+
+            writeln!(printer).expect("write fail");
+        }
     }
 }
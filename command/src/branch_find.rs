@@ -1,4 +1,4 @@
-use crate::{Command, CommandBoxClone, WorkOption, WorkResult};
+use crate::{BranchPattern, Command, CommandBoxClone, WorkOption, WorkResult};
 use color_printer::{Color, ColorPrinter, ColorSpec};
 use command_derive::CommandBoxClone;
 use gitlib::GitRepo;
@@ -6,32 +6,41 @@ use std::{io::Write, path::PathBuf};
 
 #[derive(Clone, CommandBoxClone)]
 pub struct BranchFindCommand {
-    branch: String,
+    pattern: BranchPattern,
+    remotes: bool,
 }
 
 impl BranchFindCommand {
-    pub fn new(branch: String) -> Self {
-        Self { branch }
+    pub fn new(pattern: BranchPattern, remotes: bool) -> Self {
+        Self { pattern, remotes }
     }
 }
 
 struct BranchFindCommandResult {
-    branch: String,
+    branches: Vec<String>,
     path: PathBuf,
 }
 
 impl Command for BranchFindCommand {
     fn process(&self, repo: GitRepo) -> WorkOption {
-        if let Ok(()) = repo.has_local_branch(&self.branch) {
-            let result = Box::new(BranchFindCommandResult {
-                branch: self.branch.clone(),
-                path: repo.path().into(),
-            });
-
-            Some(result)
-        } else {
-            None
+        let branches = repo
+            .branches(self.remotes)
+            .ok()?
+            .into_iter()
+            .filter(|x| self.pattern.matches(x.name()))
+            .map(|x| x.name().into())
+            .collect::<Vec<_>>();
+
+        if branches.is_empty() {
+            return None;
         }
+
+        let result = Box::new(BranchFindCommandResult {
+            branches,
+            path: repo.path().into(),
+        });
+
+        Some(result)
     }
 }
 
@@ -41,7 +50,9 @@ impl WorkResult for BranchFindCommandResult {
         cs.set_intense(true);
         cs.set_fg(Some(Color::Green));
 
-        printer.color_context(&cs, |h| write!(h, " {}", self.branch).expect("write fail"));
+        printer.color_context(&cs, |h| {
+            write!(h, " {}", self.branches.join(", ")).expect("write fail")
+        });
 
         writeln!(printer, " - {}", self.path.display()).expect("write fail");
     }
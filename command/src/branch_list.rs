@@ -0,0 +1,108 @@
+use crate::{format_age, Command, CommandBoxClone, WorkOption, WorkResult};
+use color_printer::{Color, ColorPrinter, ColorSpec};
+use command_derive::CommandBoxClone;
+use gitlib::{BranchInfo, GitRepo};
+use std::{io::Write, path::PathBuf};
+
+#[derive(Clone, CommandBoxClone, Default)]
+pub struct BranchListCommand;
+
+impl BranchListCommand {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+struct BranchListCommandResult {
+    path: PathBuf,
+    branches: Vec<BranchInfo>,
+}
+
+impl Command for BranchListCommand {
+    fn process(&self, repo: GitRepo) -> WorkOption {
+        let branches = repo
+            .branches(false)
+            .ok()?
+            .iter()
+            .filter_map(|x| repo.branch_info(x.name()).ok())
+            .collect::<Vec<_>>();
+
+        if branches.is_empty() {
+            return None;
+        }
+
+        let result = BranchListCommandResult {
+            path: repo.path().into(),
+            branches,
+        };
+
+        Some(Box::new(result))
+    }
+}
+
+fn tracking(branch: &BranchInfo) -> String {
+    match (branch.ahead(), branch.behind()) {
+        (0, 0) => String::new(),
+        (ahead, 0) => format!("[ahead {}]", ahead),
+        (0, behind) => format!("[behind {}]", behind),
+        (ahead, behind) => format!("[ahead {}, behind {}]", ahead, behind),
+    }
+}
+
+impl WorkResult for BranchListCommandResult {
+    fn print(&self, printer: &mut ColorPrinter<'_>) {
+        writeln!(printer, "{}", self.path.display()).expect("write fail");
+
+        let name_width = self.branches.iter().map(|x| x.name().len()).max();
+        let upstream_width = self
+            .branches
+            .iter()
+            .map(|x| x.upstream().unwrap_or_default().len())
+            .max();
+        let tracking_width = self.branches.iter().map(|x| tracking(x).len()).max();
+
+        let mut cs = ColorSpec::new();
+        cs.set_intense(true);
+
+        for branch in &self.branches {
+            let marker = if branch.is_head() { '*' } else { ' ' };
+            let color = if branch.is_head() {
+                Color::Green
+            } else {
+                Color::Yellow
+            };
+
+            cs.set_fg(Some(color));
+
+            printer.color_context(&cs, |h| {
+                write!(
+                    h,
+                    " {} {:width$}",
+                    marker,
+                    branch.name(),
+                    width = name_width.unwrap_or_default()
+                )
+                .expect("write fail")
+            });
+
+            write!(
+                printer,
+                " {:upstream$} {:tracking$} {} by {}",
+                branch.upstream().unwrap_or_default(),
+                tracking(branch),
+                format_age(branch.time()),
+                branch.author(),
+                upstream = upstream_width.unwrap_or_default(),
+                tracking = tracking_width.unwrap_or_default(),
+            )
+            .expect("write fail");
+
+            if branch.is_merged() {
+                cs.set_fg(Some(Color::Cyan));
+                printer.color_context(&cs, |h| write!(h, " (merged)").expect("write fail"));
+            }
+
+            writeln!(printer).expect("write fail");
+        }
+    }
+}
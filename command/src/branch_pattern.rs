@@ -0,0 +1,41 @@
+use glob::Pattern;
+use regex::Regex;
+
+/// How branch names are matched by the branch commands. Names without any
+/// glob characters match exactly.
+#[derive(Clone, Debug)]
+pub enum BranchPattern {
+    Exact(String),
+    Glob(Pattern),
+    Regex(Regex),
+}
+
+impl BranchPattern {
+    pub fn new(pattern: &str, is_regex: bool) -> Result<Self, String> {
+        if is_regex {
+            return Regex::new(pattern)
+                .map(BranchPattern::Regex)
+                .map_err(|e| e.to_string());
+        }
+
+        if !pattern.contains(&['*', '?', '['][..]) {
+            return Ok(BranchPattern::Exact(pattern.into()));
+        }
+
+        Pattern::new(pattern)
+            .map(BranchPattern::Glob)
+            .map_err(|e| e.to_string())
+    }
+
+    pub fn is_exact(&self) -> bool {
+        matches!(self, BranchPattern::Exact(_))
+    }
+
+    pub fn matches(&self, branch: &str) -> bool {
+        match self {
+            BranchPattern::Exact(name) => name == branch,
+            BranchPattern::Glob(pattern) => pattern.matches(branch),
+            BranchPattern::Regex(regex) => regex.is_match(branch),
+        }
+    }
+}
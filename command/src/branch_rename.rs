@@ -0,0 +1,102 @@
+use crate::{Command, CommandBoxClone, WorkOption, WorkResult};
+use color_printer::{Color, ColorPrinter, ColorSpec};
+use command_derive::CommandBoxClone;
+use gitlib::{self, GitRepo};
+use std::{io::Write, path::PathBuf};
+
+const REMOTE: &str = "origin";
+
+#[derive(Clone, CommandBoxClone)]
+pub struct BranchRenameCommand {
+    branch: String,
+    new_name: String,
+    remote: bool,
+}
+
+impl BranchRenameCommand {
+    pub fn new(branch: String, new_name: String, remote: bool) -> Self {
+        Self {
+            branch,
+            new_name,
+            remote,
+        }
+    }
+}
+
+struct BranchRenameCommandResult {
+    path: PathBuf,
+    branch: String,
+    new_name: String,
+    conflict: bool,
+    renamed_remote: bool,
+    error: Option<gitlib::Error>,
+}
+
+impl Command for BranchRenameCommand {
+    fn process(&self, repo: GitRepo) -> WorkOption {
+        let mut result = BranchRenameCommandResult {
+            path: repo.path().into(),
+            branch: self.branch.clone(),
+            new_name: self.new_name.clone(),
+            conflict: false,
+            renamed_remote: false,
+            error: None,
+        };
+
+        match repo.rename_branch(&self.branch, &self.new_name) {
+            Ok(()) => {}
+            Err(gitlib::Error::NotFound) => return None,
+            Err(gitlib::Error::Exists) => {
+                result.conflict = true;
+                return Some(Box::new(result));
+            }
+            Err(e) => {
+                result.error = Some(e);
+                return Some(Box::new(result));
+            }
+        }
+
+        if self.remote {
+            match repo.rename_remote_branch(REMOTE, &self.branch, &self.new_name) {
+                Ok(()) => result.renamed_remote = true,
+                Err(gitlib::Error::NotFound) => {}
+                Err(e) => result.error = Some(e),
+            }
+        }
+
+        Some(Box::new(result))
+    }
+}
+
+impl WorkResult for BranchRenameCommandResult {
+    fn print(&self, printer: &mut ColorPrinter<'_>) {
+        let mut cs = ColorSpec::new();
+        cs.set_intense(true);
+
+        let color = if self.conflict || self.error.is_some() {
+            Color::Red
+        } else {
+            Color::Green
+        };
+
+        cs.set_fg(Some(color));
+
+        printer.color_context(&cs, |h| {
+            write!(h, " {} -> {}", self.branch, self.new_name).expect("write fail");
+
+            if self.conflict {
+                write!(h, " - CONFLICT, {} already exists", self.new_name).expect("write fail");
+            }
+
+            if self.renamed_remote {
+                write!(h, " - renamed on {}", REMOTE).expect("write fail");
+            }
+
+            if let Some(ref e) = self.error {
+                write!(h, " - ERROR {:?}", e).expect("write fail");
+            }
+        });
+
+        writeln!(printer, " - {}", self.path.display()).expect("write fail");
+    }
+}
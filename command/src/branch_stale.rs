@@ -0,0 +1,199 @@
+use crate::{
+    branch_delete::verdict, format_age, Command, CommandBoxClone, Verdict, WorkOption, WorkResult,
+};
+use color_printer::{Color, ColorPrinter, ColorSpec};
+use command_derive::CommandBoxClone;
+use gitlib::{self, BranchInfo, GitRepo};
+use std::{
+    io::Write,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+#[derive(Clone, CommandBoxClone)]
+pub struct BranchStaleCommand {
+    max_age: Duration,
+    prune: bool,
+    force: bool,
+}
+
+impl BranchStaleCommand {
+    pub fn new(max_age: Duration, prune: bool, force: bool) -> Self {
+        Self {
+            max_age,
+            prune,
+            force,
+        }
+    }
+
+    /// Branches whose tip is older than `max_age`, or whose upstream is gone.
+    fn stale_branches(&self, repo: &GitRepo) -> Result<Vec<StaleBranch>, gitlib::Error> {
+        let cutoff = SystemTime::now()
+            .checked_sub(self.max_age)
+            .unwrap_or(UNIX_EPOCH);
+        let default_branch = repo.default_branch_name().ok();
+        let mut stale = Vec::new();
+
+        for branch in repo.branches(false)? {
+            let info = repo.branch_info(branch.name())?;
+
+            if !info.is_upstream_gone() && info.time() >= cutoff {
+                continue;
+            }
+
+            let verdict = if self.prune {
+                Some(verdict(
+                    repo,
+                    &branch,
+                    default_branch.as_deref(),
+                    false,
+                    self.force,
+                )?)
+            } else {
+                None
+            };
+
+            stale.push(StaleBranch {
+                info,
+                verdict,
+                deleted: false,
+                error: None,
+            });
+        }
+
+        Ok(stale)
+    }
+}
+
+struct StaleBranch {
+    info: BranchInfo,
+    verdict: Option<Verdict>,
+    deleted: bool,
+    error: Option<gitlib::Error>,
+}
+
+struct BranchStaleCommandResult {
+    path: PathBuf,
+    branches: Vec<StaleBranch>,
+    is_plan: bool,
+}
+
+struct BranchStaleErrorResult {
+    path: PathBuf,
+    error: gitlib::Error,
+}
+
+fn error_result(repo: &GitRepo, error: gitlib::Error) -> WorkOption {
+    Some(Box::new(BranchStaleErrorResult {
+        path: repo.path().into(),
+        error,
+    }))
+}
+
+impl Command for BranchStaleCommand {
+    fn process(&self, repo: GitRepo) -> WorkOption {
+        let mut branches = match self.stale_branches(&repo) {
+            Ok(branches) => branches,
+            Err(e) => return error_result(&repo, e),
+        };
+
+        if branches.is_empty() {
+            return None;
+        }
+
+        for branch in branches
+            .iter_mut()
+            .filter(|x| x.verdict == Some(Verdict::Delete))
+        {
+            match repo.delete_local_branch(branch.info.name()) {
+                Ok(()) => branch.deleted = true,
+                Err(e) => branch.error = Some(e),
+            }
+        }
+
+        let result = BranchStaleCommandResult {
+            path: repo.path().into(),
+            branches,
+            is_plan: false,
+        };
+
+        Some(Box::new(result))
+    }
+
+    fn plan(&self, repo: &GitRepo) -> WorkOption {
+        let branches = match self.stale_branches(repo) {
+            Ok(branches) => branches,
+            Err(e) => return error_result(repo, e),
+        };
+
+        if branches.is_empty() {
+            return None;
+        }
+
+        let result = BranchStaleCommandResult {
+            path: repo.path().into(),
+            branches,
+            is_plan: true,
+        };
+
+        Some(Box::new(result))
+    }
+}
+
+impl WorkResult for BranchStaleCommandResult {
+    fn print(&self, printer: &mut ColorPrinter<'_>) {
+        writeln!(printer, "{}", self.path.display()).expect("write fail");
+
+        let mut cs = ColorSpec::new();
+        cs.set_intense(true);
+
+        for branch in &self.branches {
+            cs.set_fg(Some(Color::Yellow));
+
+            printer.color_context(&cs, |h| {
+                if self.is_plan && branch.verdict == Some(Verdict::Delete) {
+                    write!(h, " Delete").expect("write fail");
+                }
+
+                write!(h, " {}", branch.info.name()).expect("write fail")
+            });
+
+            write!(printer, " - {}", format_age(branch.info.time())).expect("write fail");
+
+            if branch.info.is_upstream_gone() {
+                write!(printer, " [gone]").expect("write fail");
+            }
+
+            let status = match (branch.verdict, branch.deleted, &branch.error) {
+                (_, _, Some(e)) => Some((format!("ERROR {:?}", e), Color::Red)),
+                (_, true, _) => Some(("deleted".into(), Color::Red)),
+                (Some(Verdict::Unmerged), _, _) => {
+                    Some(("NOT MERGED, use --force".into(), Color::Magenta))
+                }
+                (Some(Verdict::Protected), _, _) => Some(("PROTECTED".into(), Color::Magenta)),
+                _ => None,
+            };
+
+            if let Some((status, color)) = status {
+                cs.set_fg(Some(color));
+                printer.color_context(&cs, |h| write!(h, " - {}", status).expect("write fail"));
+            }
+
+            writeln!(printer).expect("write fail");
+        }
+    }
+}
+
+impl WorkResult for BranchStaleErrorResult {
+    fn print(&self, printer: &mut ColorPrinter<'_>) {
+        let mut cs = ColorSpec::new();
+        cs.set_intense(true);
+        cs.set_fg(Some(Color::Red));
+
+        printer.color_context(&cs, |h| {
+            write!(h, " ERROR {:?}", self.error).expect("write fail")
+        });
+
+        writeln!(printer, " - {}", self.path.display()).expect("write fail");
+    }
+}
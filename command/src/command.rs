@@ -7,6 +7,12 @@ pub trait CommandBoxClone {
 
 pub trait Command: Send + CommandBoxClone {
     fn process(&self, repo: GitRepo) -> WorkOption;
+
+    /// Describe what `process` would change in `repo` without touching it.
+    /// Commands that return `None` for every repo have nothing to confirm.
+    fn plan(&self, _repo: &GitRepo) -> WorkOption {
+        None
+    }
 }
 
 pub type WorkOption = Option<Box<dyn WorkResult>>;
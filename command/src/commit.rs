@@ -0,0 +1,110 @@
+use crate::{Command, CommandBoxClone, WorkOption, WorkResult};
+use color_printer::{Color, ColorPrinter, ColorSpec};
+use command_derive::CommandBoxClone;
+use gitlib::{self, CommitOutcome, GitRepo};
+use std::{io::Write, path::PathBuf};
+
+#[derive(Clone, CommandBoxClone)]
+pub struct CommitCommand {
+    message: String,
+    all: bool,
+    amend: bool,
+}
+
+impl CommitCommand {
+    pub fn new(message: String, all: bool, amend: bool) -> Self {
+        Self {
+            message,
+            all,
+            amend,
+        }
+    }
+}
+
+struct CommitCommandResult {
+    path: PathBuf,
+    outcome: Result<CommitOutcome, gitlib::Error>,
+    is_plan: bool,
+}
+
+/// Repos with nothing to commit, and bare repos, are skipped.
+fn result(
+    path: PathBuf,
+    outcome: Result<Option<CommitOutcome>, gitlib::Error>,
+    is_plan: bool,
+) -> WorkOption {
+    let outcome = match outcome {
+        Ok(Some(outcome)) => Ok(outcome),
+        Ok(None) | Err(gitlib::Error::BareRepo) => return None,
+        Err(e) => Err(e),
+    };
+
+    Some(Box::new(CommitCommandResult {
+        path,
+        outcome,
+        is_plan,
+    }))
+}
+
+impl Command for CommitCommand {
+    fn process(&self, repo: GitRepo) -> WorkOption {
+        let outcome = repo.commit(&self.message, self.all, self.amend);
+
+        result(repo.path().into(), outcome, false)
+    }
+
+    /// Only amending rewrites history, so plain commits go ahead unasked.
+    fn plan(&self, repo: &GitRepo) -> WorkOption {
+        if !self.amend {
+            return None;
+        }
+
+        let outcome = repo.preview_commit(self.all, self.amend);
+
+        result(repo.path().into(), outcome, true)
+    }
+}
+
+impl WorkResult for CommitCommandResult {
+    fn print(&self, printer: &mut ColorPrinter<'_>) {
+        let (status, color) = match self.outcome {
+            Ok(ref outcome) if self.is_plan => (
+                format!(
+                    "Amend {} with {} file(s)",
+                    outcome.amended().unwrap_or_default(),
+                    outcome.files()
+                ),
+                Color::Yellow,
+            ),
+            Ok(ref outcome) => {
+                let id = outcome.id().unwrap_or_default();
+
+                let status = match outcome.amended() {
+                    Some(amended) => format!("Amended {} as {}", amended, id),
+                    None => format!("Committed {}", id),
+                };
+
+                (
+                    format!("{} ({} file(s))", status, outcome.files()),
+                    Color::Green,
+                )
+            }
+            Err(gitlib::Error::AlreadyPushed) => {
+                ("REFUSED HEAD is already pushed".into(), Color::Red)
+            }
+            Err(gitlib::Error::Unmerged) => {
+                ("SKIPPED merge or rebase in progress".into(), Color::Yellow)
+            }
+            Err(gitlib::Error::UnbornBranch) => ("SKIPPED nothing to amend".into(), Color::Yellow),
+            Err(ref e) => (format!("ERROR {}", e), Color::Red),
+        };
+
+        let mut cs = ColorSpec::new();
+        cs.set_intense(true);
+        cs.set_fg(Some(color));
+
+        printer.color_context(&cs, |h| write!(h, " {}", status).expect("write fail"));
+
+        writeln!(printer, " - {}", self.path.display()).expect("write fail");
+    }
+}
@@ -0,0 +1,178 @@
+use crate::{Command, CommandBoxClone, WorkOption, WorkResult};
+use color_printer::{Color, ColorPrinter, ColorSpec};
+use command_derive::CommandBoxClone;
+use gitlib::{Diff, DiffRange, FileDiff, GitRepo};
+use std::{io::Write, path::PathBuf};
+
+/// Widest the `+`/`-` graph of a diffstat line gets
+const GRAPH_WIDTH: usize = 40;
+
+#[derive(Clone, CommandBoxClone)]
+pub struct DiffCommand {
+    range: DiffRange,
+    stat: bool,
+    patch: bool,
+}
+
+impl DiffCommand {
+    /// Prints a diffstat for each repo when `stat` is set, followed by full
+    /// patches when `patch` is set
+    pub fn new(range: DiffRange, stat: bool, patch: bool) -> Self {
+        Self { range, stat, patch }
+    }
+}
+
+struct DiffCommandResult {
+    path: PathBuf,
+    diff: Result<Diff, gitlib::Error>,
+    stat: bool,
+    patch: bool,
+}
+
+impl Command for DiffCommand {
+    fn process(&self, repo: GitRepo) -> WorkOption {
+        let diff = match repo.diff(&self.range, self.patch) {
+            Ok(ref diff) if diff.is_empty() => return None,
+            // Repos without the revisions, or without a work tree
+            Err(gitlib::Error::NotFound) | Err(gitlib::Error::BareRepo) => return None,
+            diff => diff,
+        };
+
+        let result = DiffCommandResult {
+            path: repo.path().into(),
+            diff,
+            stat: self.stat,
+            patch: self.patch,
+        };
+
+        Some(Box::new(result))
+    }
+}
+
+fn file_name(file: &FileDiff) -> String {
+    match file.old_path() {
+        Some(old_path) => format!("{} => {}", old_path.display(), file.path().display()),
+        None => file.path().display().to_string(),
+    }
+}
+
+impl DiffCommandResult {
+    fn print_stat(&self, printer: &mut ColorPrinter<'_>, diff: &Diff) {
+        let names = diff.files().iter().map(file_name).collect::<Vec<_>>();
+        let name_width = names.iter().map(|x| x.len()).max().unwrap_or_default();
+
+        let most_lines = diff
+            .files()
+            .iter()
+            .map(|x| x.insertions() + x.deletions())
+            .max()
+            .unwrap_or_default();
+        let count_width = most_lines.to_string().len();
+
+        // Scale the graph down like git does, keeping at least one mark for
+        // any change.
+        let scale = |lines: usize| match lines {
+            0 => 0,
+            _ if most_lines <= GRAPH_WIDTH => lines,
+            _ => (lines * GRAPH_WIDTH / most_lines).max(1),
+        };
+
+        let mut cs = ColorSpec::new();
+        cs.set_intense(true);
+
+        for (file, name) in diff.files().iter().zip(names) {
+            write!(printer, " {:width$} |", name, width = name_width).expect("write fail");
+
+            if file.is_binary() {
+                writeln!(printer, " Bin").expect("write fail");
+                continue;
+            }
+
+            let lines = file.insertions() + file.deletions();
+            write!(printer, " {:>width$}", lines, width = count_width).expect("write fail");
+
+            if lines > 0 {
+                write!(printer, " ").expect("write fail");
+            }
+
+            cs.set_fg(Some(Color::Green));
+            printer.color_context(&cs, |h| {
+                write!(h, "{}", "+".repeat(scale(file.insertions()))).expect("write fail")
+            });
+
+            cs.set_fg(Some(Color::Red));
+            printer.color_context(&cs, |h| {
+                write!(h, "{}", "-".repeat(scale(file.deletions()))).expect("write fail")
+            });
+
+            writeln!(printer).expect("write fail");
+        }
+
+        writeln!(
+            printer,
+            " {} file(s) changed, {} insertion(s)(+), {} deletion(s)(-)",
+            diff.files().len(),
+            diff.insertions(),
+            diff.deletions()
+        )
+        .expect("write fail");
+    }
+
+    fn print_patch(&self, printer: &mut ColorPrinter<'_>, diff: &Diff) {
+        let mut cs = ColorSpec::new();
+        cs.set_intense(true);
+
+        let lines = diff.files().iter().filter_map(|x| x.patch());
+
+        for line in lines.flat_map(|x| x.lines()) {
+            let color = if line.starts_with("+++") || line.starts_with("---") {
+                None
+            } else if line.starts_with('+') {
+                Some(Color::Green)
+            } else if line.starts_with('-') {
+                Some(Color::Red)
+            } else if line.starts_with("@@") {
+                Some(Color::Cyan)
+            } else {
+                None
+            };
+
+            match color {
+                Some(color) => {
+                    cs.set_fg(Some(color));
+                    printer.color_context(&cs, |h| write!(h, "{}", line).expect("write fail"));
+                    writeln!(printer).expect("write fail");
+                }
+                None => writeln!(printer, "{}", line).expect("write fail"),
+            }
+        }
+    }
+}
+
+impl WorkResult for DiffCommandResult {
+    fn print(&self, printer: &mut ColorPrinter<'_>) {
+        let diff = match self.diff {
+            Ok(ref diff) => diff,
+            Err(ref e) => {
+                let mut cs = ColorSpec::new();
+                cs.set_intense(true);
+                cs.set_fg(Some(Color::Red));
+
+                printer.color_context(&cs, |h| write!(h, " ERROR {:?}", e).expect("write fail"));
+                writeln!(printer, " - {}", self.path.display()).expect("write fail");
+
+                return;
+            }
+        };
+
+        writeln!(printer, "{}", self.path.display()).expect("write fail");
+
+        if self.stat {
+            self.print_stat(printer, diff);
+        }
+
+        if self.patch {
+            self.print_patch(printer, diff);
+        }
+    }
+}
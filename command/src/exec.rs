@@ -0,0 +1,205 @@
+use crate::{Command, CommandBoxClone, WorkOption, WorkResult};
+use color_printer::{Color, ColorPrinter, ColorSpec};
+use command_derive::CommandBoxClone;
+use gitlib::GitRepo;
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+    process::{self, Output, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+const REPO_PATH_VAR: &str = "GITPLZ_REPO_PATH";
+const BRANCH_VAR: &str = "GITPLZ_BRANCH";
+
+#[derive(Clone, CommandBoxClone)]
+pub struct ExecCommand {
+    command: Vec<String>,
+    fail_fast: bool,
+    /// Shared by every clone, so one failure is seen by all workers
+    failed: Arc<AtomicBool>,
+}
+
+impl ExecCommand {
+    /// `command` is the program followed by its arguments. With `fail_fast`,
+    /// repos that haven't started yet are skipped once the command fails.
+    pub fn new(command: Vec<String>, fail_fast: bool) -> Self {
+        Self {
+            command,
+            fail_fast,
+            failed: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+struct ExecCommandResult {
+    path: PathBuf,
+    /// `None` when skipped by `--fail-fast`
+    output: Option<io::Result<Output>>,
+}
+
+impl ExecCommand {
+    fn run(&self, repo: &GitRepo) -> Option<ExecCommandResult> {
+        let path = repo.path().to_owned();
+
+        if self.fail_fast && self.failed.load(Ordering::SeqCst) {
+            return Some(ExecCommandResult { path, output: None });
+        }
+
+        let (program, args) = self.command.split_first()?;
+
+        // The child runs inside the repo, where a relative path would be wrong
+        let repo_path = path.canonicalize().unwrap_or_else(|_| path.clone());
+
+        let mut child = process::Command::new(program);
+        child
+            .args(args)
+            .current_dir(&path)
+            .env(REPO_PATH_VAR, &repo_path)
+            // Every repo runs at once, so none of them can have the terminal
+            .stdin(Stdio::null());
+
+        let head = repo.head_state().ok();
+
+        match head.as_ref().and_then(|x| x.branch()) {
+            Some(branch) => child.env(BRANCH_VAR, branch),
+            None => child.env_remove(BRANCH_VAR),
+        };
+
+        let output = child.output();
+
+        if !output.as_ref().is_ok_and(|x| x.status.success()) {
+            self.failed.store(true, Ordering::SeqCst);
+        }
+
+        Some(ExecCommandResult {
+            path,
+            output: Some(output),
+        })
+    }
+}
+
+impl Command for ExecCommand {
+    fn process(&self, repo: GitRepo) -> WorkOption {
+        let result = self.run(&repo)?;
+
+        Some(Box::new(result))
+    }
+}
+
+fn write_output(printer: &mut ColorPrinter<'_>, output: &[u8]) {
+    if output.is_empty() {
+        return;
+    }
+
+    printer.write_all(output).expect("write fail");
+
+    if !output.ends_with(b"\n") {
+        writeln!(printer).expect("write fail");
+    }
+}
+
+impl WorkResult for ExecCommandResult {
+    fn print(&self, printer: &mut ColorPrinter<'_>) {
+        let (status, color) = match self.output {
+            None => ("SKIPPED after an earlier failure".into(), Color::Yellow),
+            Some(Err(ref e)) => (format!("ERROR {}", e), Color::Red),
+            Some(Ok(ref output)) => match output.status.code() {
+                Some(0) => ("exit 0".into(), Color::Green),
+                Some(code) => (format!("exit {}", code), Color::Red),
+                None => ("killed by a signal".into(), Color::Red),
+            },
+        };
+
+        let mut cs = ColorSpec::new();
+        cs.set_intense(true);
+        cs.set_fg(Some(color));
+
+        printer.color_context(&cs, |h| write!(h, " {}", status).expect("write fail"));
+        writeln!(printer, " - {}", self.path.display()).expect("write fail");
+
+        if let Some(Ok(ref output)) = self.output {
+            write_output(printer, &output.stdout);
+            write_output(printer, &output.stderr);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::init;
+    use tempfile::TempDir;
+
+    fn repo(dir: &TempDir, name: &str) -> GitRepo {
+        let path = dir.path().join(name);
+        init(&path);
+
+        GitRepo::open(path).unwrap()
+    }
+
+    fn sh(script: &str, fail_fast: bool) -> ExecCommand {
+        let command = vec!["sh".to_string(), "-c".to_string(), script.to_string()];
+
+        ExecCommand::new(command, fail_fast)
+    }
+
+    fn output(result: &ExecCommandResult) -> &Output {
+        match result.output {
+            Some(Ok(ref output)) => output,
+            _ => panic!("Expected the command to run"),
+        }
+    }
+
+    #[test]
+    fn captures_exit_status_and_output() {
+        let dir = TempDir::new().unwrap();
+        let repo = repo(&dir, "repo");
+
+        let result = sh("echo out; echo err >&2; exit 3", false)
+            .run(&repo)
+            .unwrap();
+        let output = output(&result);
+
+        assert_eq!(output.status.code(), Some(3));
+        assert_eq!(output.stdout, b"out\n");
+        assert_eq!(output.stderr, b"err\n");
+    }
+
+    #[test]
+    fn fail_fast_skips_repos_after_a_failure() {
+        let dir = TempDir::new().unwrap();
+        let (first, second) = (repo(&dir, "first"), repo(&dir, "second"));
+
+        let command = sh("exit 1", true);
+        assert!(command.run(&first).unwrap().output.is_some());
+        assert!(command.run(&second).unwrap().output.is_none());
+
+        let command = sh("exit 1", false);
+        assert!(command.run(&first).unwrap().output.is_some());
+        assert!(command.run(&second).unwrap().output.is_some());
+    }
+
+    #[test]
+    fn sets_repo_path_and_branch_variables() {
+        let dir = TempDir::new().unwrap();
+        let repo = repo(&dir, "repo");
+        let script = format!(
+            "printf '%s\\n%s' \"${}\" \"${}\"",
+            REPO_PATH_VAR, BRANCH_VAR
+        );
+
+        let result = sh(&script, false).run(&repo).unwrap();
+        let stdout = String::from_utf8(output(&result).stdout.clone()).unwrap();
+        let expected = format!(
+            "{}\n{}",
+            dir.path().join("repo").canonicalize().unwrap().display(),
+            repo.head_state().unwrap().branch().unwrap()
+        );
+
+        assert_eq!(stdout, expected);
+    }
+}
@@ -1,39 +1,267 @@
-use crate::{Command, CommandBoxClone, WorkOption, WorkResult};
-use color_printer::ColorPrinter;
+use crate::{format_age, Command, CommandBoxClone, WorkOption, WorkResult};
+use color_printer::{Color, ColorPrinter, ColorSpec};
 use command_derive::CommandBoxClone;
-use gitlib::GitRepo;
-use std::io::Write;
+use gitlib::{self, BranchInfo, FetchOptions, FetchResult, GitRepo};
+use std::{io::Write, path::PathBuf, time::SystemTime};
 
-#[derive(Clone, CommandBoxClone, Default)]
-pub struct FetchCommand;
+const DEFAULT_REMOTE: &str = "origin";
+
+/// Which remotes of each repo get fetched.
+#[derive(Clone, Debug)]
+pub enum FetchRemotes {
+    /// `origin`, or every remote when a repo has no `origin`
+    Default,
+    All,
+    Named(String),
+}
+
+#[derive(Clone, CommandBoxClone)]
+pub struct FetchCommand {
+    remotes: FetchRemotes,
+    options: FetchOptions,
+    quiet: bool,
+    offline: bool,
+}
 
 impl FetchCommand {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(remotes: FetchRemotes, options: FetchOptions, quiet: bool, offline: bool) -> Self {
+        Self {
+            remotes,
+            options,
+            quiet,
+            offline,
+        }
+    }
+
+    fn remote_names(&self, repo: &GitRepo) -> Result<Vec<String>, gitlib::Error> {
+        let names = repo.remote_names()?;
+
+        let names = match self.remotes {
+            FetchRemotes::All => names,
+            FetchRemotes::Named(ref name) => names.into_iter().filter(|x| x == name).collect(),
+            FetchRemotes::Default => {
+                if names.iter().any(|x| x == DEFAULT_REMOTE) {
+                    vec![DEFAULT_REMOTE.into()]
+                } else {
+                    names
+                }
+            }
+        };
+
+        Ok(names)
     }
 }
 
 struct FetchCommandResult {
-    msg: String,
+    path: PathBuf,
+    remotes: Vec<(String, Result<FetchResult, gitlib::Error>)>,
+    retries: usize,
+}
+
+/// Where the checked out branch stands as of the last fetch
+struct FetchOfflineResult {
+    path: PathBuf,
+    branch: String,
+    info: Result<BranchInfo, gitlib::Error>,
+    last_fetched: Option<SystemTime>,
+}
+
+impl FetchCommand {
+    fn process_offline(&self, repo: GitRepo) -> WorkOption {
+        let branch: String = repo
+            .head()
+            .map(|x| x.short_name().into())
+            .unwrap_or_default();
+        let info = repo.branch_info(&branch);
+
+        let is_up_to_date = match info {
+            Ok(ref info) => info.upstream().is_some() && info.behind() == 0,
+            Err(_) => false,
+        };
+
+        if self.quiet && is_up_to_date {
+            return None;
+        }
+
+        let result = FetchOfflineResult {
+            path: repo.path().into(),
+            branch,
+            info,
+            last_fetched: repo.last_fetched(),
+        };
+
+        Some(Box::new(result))
+    }
 }
 
 impl Command for FetchCommand {
     fn process(&self, repo: GitRepo) -> WorkOption {
-        let res = match repo.fetch() {
-            Ok(_) => Box::new(FetchCommandResult {
-                msg: "My man!".into(),
-            }),
-            Err(e) => Box::new(FetchCommandResult {
-                msg: format!("{:?}", e),
-            }),
+        if self.offline {
+            return self.process_offline(repo);
+        }
+
+        let remotes = match self.remote_names(&repo) {
+            Ok(names) => names
+                .into_iter()
+                .map(|name| {
+                    let result = repo.fetch(&name, &self.options);
+                    (name, result)
+                })
+                .collect::<Vec<_>>(),
+            Err(e) => vec![(String::new(), Err(e))],
+        };
+
+        let is_up_to_date = remotes.iter().all(|(_, result)| match result {
+            Ok(fetch) => fetch.is_up_to_date(),
+            Err(_) => false,
+        });
+
+        if remotes.is_empty() || (self.quiet && is_up_to_date) {
+            return None;
+        }
+
+        let result = FetchCommandResult {
+            path: repo.path().into(),
+            remotes,
+            retries: repo.network_retries(),
         };
 
-        Some(res)
+        Some(Box::new(result))
+    }
+}
+
+fn short_id(id: &str) -> &str {
+    &id[..id.len().min(7)]
+}
+
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
     }
 }
 
 impl WorkResult for FetchCommandResult {
     fn print(&self, printer: &mut ColorPrinter<'_>) {
-        writeln!(printer, "{}", self.msg).expect("write fail");
+        write!(printer, "{}", self.path.display()).expect("write fail");
+
+        let mut cs = ColorSpec::new();
+        cs.set_intense(true);
+
+        if self.retries > 0 {
+            cs.set_fg(Some(Color::Yellow));
+            printer.color_context(&cs, |h| {
+                write!(h, " - retried {} time(s)", self.retries).expect("write fail")
+            });
+        }
+
+        writeln!(printer).expect("write fail");
+
+        for (remote, result) in &self.remotes {
+            let fetch = match result {
+                Ok(fetch) => fetch,
+                Err(e) => {
+                    cs.set_fg(Some(Color::Red));
+                    printer.color_context(&cs, |h| {
+                        write!(h, " {} - ERROR {}", remote, e).expect("write fail")
+                    });
+                    writeln!(printer).expect("write fail");
+                    continue;
+                }
+            };
+
+            if fetch.is_up_to_date() {
+                writeln!(printer, " {} - up to date", remote).expect("write fail");
+                continue;
+            }
+
+            writeln!(
+                printer,
+                " {} - {} objects, {}",
+                remote,
+                fetch.received_objects(),
+                format_bytes(fetch.received_bytes())
+            )
+            .expect("write fail");
+
+            let name_width = fetch
+                .updated_refs()
+                .iter()
+                .map(|x| x.name().len())
+                .max()
+                .unwrap_or_default();
+
+            for updated in fetch.updated_refs() {
+                let (status, color, ids) = match (updated.old_id(), updated.new_id()) {
+                    (None, Some(new)) => ("          New", Color::Green, short_id(new).into()),
+                    (Some(old), None) => ("       Pruned", Color::Red, short_id(old).into()),
+                    (Some(old), Some(new)) => (
+                        "      Updated",
+                        Color::Cyan,
+                        format!("{}..{}", short_id(old), short_id(new)),
+                    ),
+                    (None, None) => continue,
+                };
+
+                cs.set_fg(Some(color));
+                printer.color_context(&cs, |h| write!(h, " {}", status).expect("write fail"));
+                writeln!(
+                    printer,
+                    " {:width$} {}",
+                    updated.name(),
+                    ids,
+                    width = name_width
+                )
+                .expect("write fail");
+            }
+        }
+    }
+}
+
+impl WorkResult for FetchOfflineResult {
+    fn print(&self, printer: &mut ColorPrinter<'_>) {
+        let (status, color) = match self.info {
+            Ok(ref info) if info.is_upstream_gone() => (
+                format!("{} is gone", info.upstream().unwrap_or_default()),
+                Some(Color::Yellow),
+            ),
+            Ok(ref info) => match (info.upstream(), info.ahead(), info.behind()) {
+                (None, _, _) => ("no upstream".into(), Some(Color::Yellow)),
+                (Some(upstream), 0, 0) => (format!("up to date with {}", upstream), None),
+                (Some(upstream), ahead, behind) => (
+                    format!("ahead {}, behind {} of {}", ahead, behind, upstream),
+                    Some(Color::Cyan),
+                ),
+            },
+            Err(gitlib::Error::NotFound) => ("detached HEAD".into(), Some(Color::Yellow)),
+            Err(ref e) => (format!("ERROR {}", e), Some(Color::Red)),
+        };
+
+        let mut cs = ColorSpec::new();
+        cs.set_intense(true);
+        cs.set_fg(color);
+
+        printer.color_context(&cs, |h| {
+            write!(h, " {} - {}", self.branch, status).expect("write fail")
+        });
+
+        match self.last_fetched {
+            Some(time) => write!(printer, ", fetched {}", format_age(time)),
+            None => write!(printer, ", never fetched"),
+        }
+        .expect("write fail");
+
+        writeln!(printer, " - {}", self.path.display()).expect("write fail");
     }
 }
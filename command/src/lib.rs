@@ -2,6 +2,40 @@ command_mods::make_mods!();
 
 #[cfg(test)]
 mod tests {
+    use std::path::Path;
+
+    /// Stage every file in the work tree and commit it on HEAD
+    pub(crate) fn commit_all(repo: &git2::Repository, message: &str) -> git2::Oid {
+        let mut index = repo.index().unwrap();
+        index
+            .add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None)
+            .unwrap();
+        index.write().unwrap();
+
+        let signature = git2::Signature::now("git-plz", "git-plz@example.com").unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let parent = repo.head().ok().map(|x| x.peel_to_commit().unwrap());
+        let parents = parent.iter().collect::<Vec<_>>();
+
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )
+        .unwrap()
+    }
+
+    /// A new repo at `path` with whatever is already there committed
+    pub(crate) fn init(path: &Path) -> git2::Repository {
+        let repo = git2::Repository::init(path).unwrap();
+        commit_all(&repo, "initial");
+
+        repo
+    }
+
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
@@ -0,0 +1,125 @@
+use crate::{Command, CommandBoxClone, WorkOption, WorkResult};
+use color_printer::{Color, ColorPrinter, ColorSpec};
+use command_derive::CommandBoxClone;
+use gitlib::{self, FetchOptions, GitRepo, MergeOutcome, NetworkOptions};
+use std::{io::Write, path::PathBuf};
+
+#[derive(Clone, CommandBoxClone)]
+pub struct PullCommand {
+    rebase: bool,
+    network: NetworkOptions,
+    offline: bool,
+}
+
+impl PullCommand {
+    pub fn new(rebase: bool, network: NetworkOptions, offline: bool) -> Self {
+        Self {
+            rebase,
+            network,
+            offline,
+        }
+    }
+}
+
+struct PullCommandResult {
+    path: PathBuf,
+    branch: String,
+    outcome: Result<MergeOutcome, gitlib::Error>,
+    retries: usize,
+    offline: bool,
+}
+
+impl Command for PullCommand {
+    fn process(&self, repo: GitRepo) -> WorkOption {
+        let branch = repo
+            .head()
+            .map(|x| x.short_name().into())
+            .unwrap_or_default();
+
+        if self.offline {
+            let result = PullCommandResult {
+                path: repo.path().into(),
+                branch,
+                outcome: repo.preview_merge_upstream(self.rebase),
+                retries: 0,
+                offline: true,
+            };
+
+            return Some(Box::new(result));
+        }
+
+        // Repos without an upstream are still reported by `merge_upstream`.
+        let fetch = match repo.head_upstream_remote() {
+            Ok(remote) => {
+                let options = FetchOptions::new().network(self.network.clone());
+                repo.fetch(&remote, &options).map(|_| ())
+            }
+            Err(_) => Ok(()),
+        };
+
+        let outcome = fetch.and_then(|_| repo.merge_upstream(self.rebase));
+
+        let result = PullCommandResult {
+            path: repo.path().into(),
+            branch,
+            outcome,
+            retries: repo.network_retries(),
+            offline: false,
+        };
+
+        Some(Box::new(result))
+    }
+}
+
+fn short_id(id: &str) -> &str {
+    &id[..id.len().min(7)]
+}
+
+impl WorkResult for PullCommandResult {
+    fn print(&self, printer: &mut ColorPrinter<'_>) {
+        let (status, color) = match self.outcome {
+            Ok(MergeOutcome::UpToDate) => ("up to date".into(), None),
+            Ok(MergeOutcome::FastForwarded { ref from, ref to }) if self.offline => (
+                format!("would fast-forward {}..{}", short_id(from), short_id(to)),
+                Some(Color::Green),
+            ),
+            Ok(MergeOutcome::FastForwarded { ref from, ref to }) => (
+                format!("fast-forwarded {}..{}", short_id(from), short_id(to)),
+                Some(Color::Green),
+            ),
+            Ok(MergeOutcome::Rebased { commits }) if self.offline => (
+                format!("would rebase {} commit(s)", commits),
+                Some(Color::Green),
+            ),
+            Ok(MergeOutcome::Rebased { commits }) => {
+                (format!("rebased {} commit(s)", commits), Some(Color::Green))
+            }
+            Ok(MergeOutcome::Diverged { ahead, behind }) => (
+                format!(
+                    "SKIPPED diverged (ahead {}, behind {}), use --rebase",
+                    ahead, behind
+                ),
+                Some(Color::Yellow),
+            ),
+            Ok(MergeOutcome::Dirty) => ("SKIPPED dirty".into(), Some(Color::Yellow)),
+            Ok(MergeOutcome::Detached) => ("SKIPPED detached HEAD".into(), Some(Color::Yellow)),
+            Ok(MergeOutcome::NoUpstream) => ("SKIPPED no upstream".into(), Some(Color::Yellow)),
+            Ok(MergeOutcome::Conflict) => ("CONFLICT, rebase aborted".into(), Some(Color::Red)),
+            Err(ref e) => (format!("ERROR {:?}", e), Some(Color::Red)),
+        };
+
+        let mut cs = ColorSpec::new();
+        cs.set_intense(true);
+        cs.set_fg(color);
+
+        printer.color_context(&cs, |h| {
+            write!(h, " {} - {}", self.branch, status).expect("write fail");
+
+            if self.retries > 0 {
+                write!(h, " - retried {} time(s)", self.retries).expect("write fail");
+            }
+        });
+
+        writeln!(printer, " - {}", self.path.display()).expect("write fail");
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{Command, CommandBoxClone, WorkOption, WorkResult};
+use color_printer::{Color, ColorPrinter, ColorSpec};
+use command_derive::CommandBoxClone;
+use gitlib::{self, GitRepo, NetworkOptions, PushOutcome};
+use std::{io::Write, path::PathBuf};
+
+#[derive(Clone, CommandBoxClone)]
+pub struct PushCommand {
+    set_upstream: bool,
+    network: NetworkOptions,
+    offline: bool,
+}
+
+impl PushCommand {
+    pub fn new(set_upstream: bool, network: NetworkOptions, offline: bool) -> Self {
+        Self {
+            set_upstream,
+            network,
+            offline,
+        }
+    }
+}
+
+struct PushCommandResult {
+    path: PathBuf,
+    branch: String,
+    outcome: Result<PushOutcome, gitlib::Error>,
+    retries: usize,
+    offline: bool,
+}
+
+impl Command for PushCommand {
+    fn process(&self, repo: GitRepo) -> WorkOption {
+        let branch = repo
+            .head()
+            .map(|x| x.short_name().into())
+            .unwrap_or_default();
+
+        let outcome = if self.offline {
+            repo.preview_push_head(self.set_upstream)
+        } else {
+            repo.push_head(self.set_upstream, &self.network)
+        };
+
+        let result = PushCommandResult {
+            path: repo.path().into(),
+            branch,
+            outcome,
+            retries: repo.network_retries(),
+            offline: self.offline,
+        };
+
+        Some(Box::new(result))
+    }
+}
+
+impl WorkResult for PushCommandResult {
+    fn print(&self, printer: &mut ColorPrinter<'_>) {
+        let (status, color) = match self.outcome {
+            Ok(PushOutcome::Pushed { ref upstream }) if self.offline => {
+                (format!("would push to {}", upstream), Some(Color::Green))
+            }
+            Ok(PushOutcome::Pushed { ref upstream }) => {
+                (format!("pushed to {}", upstream), Some(Color::Green))
+            }
+            Ok(PushOutcome::UpToDate { ref upstream }) => {
+                (format!("{} is up to date", upstream), None)
+            }
+            Ok(PushOutcome::Detached) => ("SKIPPED detached HEAD".into(), Some(Color::Yellow)),
+            Ok(PushOutcome::NoUpstream) => (
+                "SKIPPED no upstream, use --set-upstream".into(),
+                Some(Color::Yellow),
+            ),
+            Err(gitlib::Error::NotFastForward) if self.offline => (
+                "WOULD BE REJECTED non-fast-forward, pull first".into(),
+                Some(Color::Red),
+            ),
+            Err(gitlib::Error::NotFastForward) => (
+                "REJECTED non-fast-forward, pull first".into(),
+                Some(Color::Red),
+            ),
+            Err(gitlib::Error::Rejected) => ("REJECTED by remote".into(), Some(Color::Red)),
+            Err(gitlib::Error::Auth(ref message)) => {
+                (format!("AUTH FAILED {}", message), Some(Color::Red))
+            }
+            Err(ref e) => (format!("ERROR {:?}", e), Some(Color::Red)),
+        };
+
+        let mut cs = ColorSpec::new();
+        cs.set_intense(true);
+        cs.set_fg(color);
+
+        printer.color_context(&cs, |h| {
+            write!(h, " {} - {}", self.branch, status).expect("write fail");
+
+            if self.retries > 0 {
+                write!(h, " - retried {} time(s)", self.retries).expect("write fail");
+            }
+        });
+
+        writeln!(printer, " - {}", self.path.display()).expect("write fail");
+    }
+}
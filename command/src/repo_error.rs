@@ -0,0 +1,29 @@
+use crate::WorkResult;
+use color_printer::{Color, ColorPrinter, ColorSpec};
+use std::{io::Write, path::PathBuf};
+
+/// A repo that was found but couldn't be opened
+pub struct RepoErrorResult {
+    path: PathBuf,
+    error: gitlib::Error,
+}
+
+impl RepoErrorResult {
+    pub fn new(path: PathBuf, error: gitlib::Error) -> Self {
+        Self { path, error }
+    }
+}
+
+impl WorkResult for RepoErrorResult {
+    fn print(&self, printer: &mut ColorPrinter<'_>) {
+        let mut cs = ColorSpec::new();
+        cs.set_intense(true);
+        cs.set_fg(Some(Color::Red));
+
+        printer.color_context(&cs, |h| {
+            write!(h, " ERROR {}", self.error).expect("write fail")
+        });
+
+        writeln!(printer, " - {}", self.path.display()).expect("write fail");
+    }
+}
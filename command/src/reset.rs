@@ -1,7 +1,7 @@
 use crate::{Command, CommandBoxClone, WorkOption, WorkResult};
 use color_printer::{Color, ColorPrinter, ColorSpec};
 use command_derive::CommandBoxClone;
-use gitlib::{GitRepo, Status};
+use gitlib::{Change, GitRepo, StatusOptions, Statuses};
 use std::{fs, io::Write, path::PathBuf};
 
 #[derive(Clone, CommandBoxClone, Default)]
@@ -18,11 +18,26 @@ struct ResetCommandResult {
     head: String,
 }
 
+struct ResetPlanResult {
+    path: PathBuf,
+    head: String,
+    changed: usize,
+    new_files: Vec<PathBuf>,
+}
+
+fn new_files(statuses: &Statuses) -> Vec<PathBuf> {
+    statuses
+        .iter()
+        .filter(|x| x.worktree_status() == Some(Change::New))
+        .map(|x| x.path().into())
+        .collect()
+}
+
 impl Command for ResetCommand {
     fn process(&self, repo: GitRepo) -> WorkOption {
         // If we can get the status of the repo, try that first
         // instead of blindly resetting when it's not required.
-        let status_result = repo.statuses();
+        let status_result = repo.statuses(&StatusOptions::new());
 
         let statuses = match status_result {
             Err(_) => None,
@@ -37,21 +52,8 @@ impl Command for ResetCommand {
 
         // Check for any 'new' files to delete
         if let Some(s) = statuses {
-            let iter = s
-                .iter()
-                .filter(|x| {
-                    for status in x.iter() {
-                        if let (_, Status::New) = status {
-                            return true;
-                        }
-                    }
-
-                    false
-                })
-                .map(|x| repo.path().join(x.path()));
-
-            for path in iter {
-                fs::remove_file(path).expect("Could not remove file");
+            for path in new_files(&s) {
+                fs::remove_file(repo.path().join(path)).expect("Could not remove file");
             }
         }
 
@@ -68,6 +70,26 @@ impl Command for ResetCommand {
 
         Some(Box::new(result))
     }
+
+    fn plan(&self, repo: &GitRepo) -> WorkOption {
+        let statuses = match repo.statuses(&StatusOptions::new()) {
+            Err(_) => return None,
+            Ok(ref s) if s.is_empty() => return None,
+            Ok(s) => s,
+        };
+
+        let head = repo.head().ok()?;
+        let new_files = new_files(&statuses);
+
+        let result = ResetPlanResult {
+            path: repo.path().into(),
+            head: head.name().into(),
+            changed: statuses.len() - new_files.len(),
+            new_files,
+        };
+
+        Some(Box::new(result))
+    }
 }
 
 impl WorkResult for ResetCommandResult {
@@ -81,3 +103,28 @@ impl WorkResult for ResetCommandResult {
         writeln!(printer, " - {}", self.path.display()).expect("write fail");
     }
 }
+
+impl WorkResult for ResetPlanResult {
+    fn print(&self, printer: &mut ColorPrinter<'_>) {
+        let mut cs = ColorSpec::new();
+        cs.set_intense(true);
+        cs.set_fg(Some(Color::Yellow));
+
+        printer.color_context(&cs, |h| write!(h, " {}", self.head).expect("write fail"));
+
+        writeln!(
+            printer,
+            " - {} ({} changed file(s) reset)",
+            self.path.display(),
+            self.changed
+        )
+        .expect("write fail");
+
+        cs.set_fg(Some(Color::Red));
+
+        for path in &self.new_files {
+            printer.color_context(&cs, |h| write!(h, "          Delete").expect("write fail"));
+            writeln!(printer, " {}", path.display()).expect("write fail");
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use crate::{format_age, Command, CommandBoxClone, WorkOption, WorkResult};
+use color_printer::{Color, ColorPrinter, ColorSpec};
+use command_derive::CommandBoxClone;
+use gitlib::{self, GitRepo, Stash};
+use std::{io::Write, path::PathBuf};
+
+#[derive(Clone, Debug)]
+pub enum StashAction {
+    List,
+    Push {
+        message: Option<String>,
+        include_untracked: bool,
+    },
+    /// Apply and drop `stash@{index}`
+    Pop(usize),
+    /// Drop `stash@{index}`
+    Drop(usize),
+}
+
+#[derive(Clone, CommandBoxClone)]
+pub struct StashCommand {
+    action: StashAction,
+}
+
+impl StashCommand {
+    pub fn new(action: StashAction) -> Self {
+        Self { action }
+    }
+}
+
+struct StashListResult {
+    path: PathBuf,
+    stashes: Vec<Stash>,
+}
+
+struct StashCommandResult {
+    path: PathBuf,
+    action: StashAction,
+    stash: Result<Stash, gitlib::Error>,
+}
+
+struct StashPlanResult {
+    path: PathBuf,
+    action: StashAction,
+    stash: Stash,
+}
+
+/// The stash `pop` or `drop` would act on, when the repo has one at `index`
+fn find_stash(repo: &GitRepo, index: usize) -> Option<Stash> {
+    repo.stashes().ok()?.into_iter().nth(index)
+}
+
+impl Command for StashCommand {
+    fn process(&self, mut repo: GitRepo) -> WorkOption {
+        let path = repo.path().to_owned();
+
+        let stash = match self.action {
+            StashAction::List => {
+                let stashes = repo.stashes().ok()?;
+
+                if stashes.is_empty() {
+                    return None;
+                }
+
+                return Some(Box::new(StashListResult { path, stashes }));
+            }
+            StashAction::Push {
+                ref message,
+                include_untracked,
+            } => match repo.stash_push(message.as_deref(), include_untracked) {
+                Ok(Some(stash)) => Ok(stash),
+                Ok(None) | Err(gitlib::Error::BareRepo) => return None,
+                Err(e) => Err(e),
+            },
+            StashAction::Pop(index) => {
+                let stash = find_stash(&repo, index)?;
+                repo.stash_pop(index).map(|_| stash)
+            }
+            StashAction::Drop(index) => {
+                let stash = find_stash(&repo, index)?;
+                repo.stash_drop(index).map(|_| stash)
+            }
+        };
+
+        let result = StashCommandResult {
+            path,
+            action: self.action.clone(),
+            stash,
+        };
+
+        Some(Box::new(result))
+    }
+
+    fn plan(&self, repo: &GitRepo) -> WorkOption {
+        let stash = match self.action {
+            StashAction::Pop(index) | StashAction::Drop(index) => find_stash(repo, index)?,
+            _ => return None,
+        };
+
+        let result = StashPlanResult {
+            path: repo.path().into(),
+            action: self.action.clone(),
+            stash,
+        };
+
+        Some(Box::new(result))
+    }
+}
+
+impl WorkResult for StashListResult {
+    fn print(&self, printer: &mut ColorPrinter<'_>) {
+        writeln!(printer, "{}", self.path.display()).expect("write fail");
+
+        let branches = self
+            .stashes
+            .iter()
+            .map(|x| x.branch().unwrap_or("(no branch)"))
+            .collect::<Vec<_>>();
+        let ages = self
+            .stashes
+            .iter()
+            .map(|x| format_age(x.time()))
+            .collect::<Vec<_>>();
+
+        let branch_width = branches.iter().map(|x| x.len()).max().unwrap_or_default();
+        let age_width = ages.iter().map(|x| x.len()).max().unwrap_or_default();
+
+        let mut cs = ColorSpec::new();
+        cs.set_intense(true);
+        cs.set_fg(Some(Color::Yellow));
+
+        for ((stash, branch), age) in self.stashes.iter().zip(branches).zip(ages) {
+            printer.color_context(&cs, |h| {
+                write!(h, " stash@{{{}}}", stash.index()).expect("write fail")
+            });
+
+            writeln!(
+                printer,
+                " {:branch$} {:age$} {}",
+                branch,
+                age,
+                stash.message(),
+                branch = branch_width,
+                age = age_width
+            )
+            .expect("write fail");
+        }
+    }
+}
+
+impl WorkResult for StashCommandResult {
+    fn print(&self, printer: &mut ColorPrinter<'_>) {
+        let done = match self.action {
+            StashAction::List => return,
+            StashAction::Push { .. } => "Stashed",
+            StashAction::Pop(_) => "Popped",
+            StashAction::Drop(_) => "Dropped",
+        };
+
+        let (status, color) = match self.stash {
+            Ok(ref stash) => (
+                format!("{} stash@{{{}}} {}", done, stash.index(), stash.message()),
+                Color::Green,
+            ),
+            Err(gitlib::Error::Conflict) => {
+                ("CONFLICT would overwrite local changes".into(), Color::Red)
+            }
+            Err(ref e) => (format!("ERROR {:?}", e), Color::Red),
+        };
+
+        let mut cs = ColorSpec::new();
+        cs.set_intense(true);
+        cs.set_fg(Some(color));
+
+        printer.color_context(&cs, |h| write!(h, " {}", status).expect("write fail"));
+
+        writeln!(printer, " - {}", self.path.display()).expect("write fail");
+    }
+}
+
+impl WorkResult for StashPlanResult {
+    fn print(&self, printer: &mut ColorPrinter<'_>) {
+        let action = match self.action {
+            StashAction::Pop(_) => "Pop",
+            StashAction::Drop(_) => "Drop",
+            _ => return,
+        };
+
+        let mut cs = ColorSpec::new();
+        cs.set_intense(true);
+        cs.set_fg(Some(Color::Yellow));
+
+        printer.color_context(&cs, |h| {
+            write!(
+                h,
+                " {} stash@{{{}}} {}",
+                action,
+                self.stash.index(),
+                self.stash.message()
+            )
+            .expect("write fail")
+        });
+
+        writeln!(printer, " - {}", self.path.display()).expect("write fail");
+    }
+}
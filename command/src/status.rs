@@ -1,79 +1,336 @@
-use crate::{Command, CommandBoxClone, WorkOption, WorkResult};
+use crate::{Command, CommandBoxClone, StatusPorcelainResult, WorkOption, WorkResult};
 use color_printer::{Color, ColorPrinter, ColorSpec};
 use command_derive::CommandBoxClone;
-use gitlib::{GitRepo, Status};
+use gitlib::{Change, GitRepo, HeadState, Operation, StatusEntry, StatusOptions, Statuses};
 use std::{io::Write, path::PathBuf};
 
+const BRANCH_WIDTH: usize = 24;
+const TRACKING_WIDTH: usize = 18;
+const STAGED_WIDTH: usize = 17;
+const WORKTREE_WIDTH: usize = 10;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum StatusFormat {
+    /// Every changed file under a header for each repo
+    #[default]
+    Long,
+    /// One line per repo
+    Short,
+    /// `git status --porcelain=v2` records under a `# repo.path` header
+    Porcelain,
+}
+
 #[derive(Clone, CommandBoxClone, Default)]
-pub struct StatusCommand;
+pub struct StatusCommand {
+    options: StatusOptions,
+    format: StatusFormat,
+    all: bool,
+    dirty_only: bool,
+}
 
 impl StatusCommand {
-    pub fn new() -> Self {
-        Self {}
+    /// Clean repos are skipped unless `all` is set and they have unpushed
+    /// commits, or the format is `Short`. `dirty_only` skips every repo
+    /// without file changes.
+    pub fn new(options: StatusOptions, format: StatusFormat, all: bool, dirty_only: bool) -> Self {
+        Self {
+            options,
+            format,
+            all,
+            dirty_only,
+        }
+    }
+}
+
+/// A changed file, and where it was before when it's a rename
+struct StatusLine {
+    path: PathBuf,
+    old_path: Option<PathBuf>,
+    staged: Option<Change>,
+    worktree: Option<Change>,
+    conflicted: bool,
+    ignored: bool,
+}
+
+impl StatusLine {
+    fn new(entry: &StatusEntry) -> Self {
+        Self {
+            path: entry.path().into(),
+            old_path: entry.old_path().map(|x| x.into()),
+            staged: entry.index_status(),
+            worktree: entry.worktree_status(),
+            conflicted: entry.is_conflicted(),
+            ignored: entry.is_ignored(),
+        }
+    }
+
+    fn staged_label(&self) -> &'static str {
+        match self.staged {
+            _ if self.conflicted => "Conflicted",
+            _ if self.ignored => "Ignored",
+            Some(Change::New) => "Staged New",
+            Some(Change::Modified) => "Staged Modified",
+            Some(Change::Deleted) => "Staged Deleted",
+            Some(Change::Renamed) => "Staged Renamed",
+            Some(Change::Typechange) => "Staged Typechange",
+            None => "",
+        }
+    }
+
+    fn worktree_label(&self) -> (&'static str, Color) {
+        match self.worktree {
+            Some(Change::New) => ("New", Color::Green),
+            Some(Change::Modified) => ("Modified", Color::Cyan),
+            Some(Change::Deleted) => ("Deleted", Color::Red),
+            Some(Change::Renamed) => ("Renamed", Color::Green),
+            Some(Change::Typechange) => ("Typechange", Color::Cyan),
+            None => ("", Color::White),
+        }
     }
 }
 
 struct StatusCommandResult {
-    statuses: Vec<(PathBuf, Status)>,
+    statuses: Vec<StatusLine>,
+    head: Option<HeadState>,
+    path: PathBuf,
+}
+
+/// One line per repo, for `--short`
+struct StatusShortResult {
+    head: Option<HeadState>,
     path: PathBuf,
+    staged: usize,
+    modified: usize,
+    untracked: usize,
+    conflicted: usize,
 }
 
 impl Command for StatusCommand {
     fn process(&self, repo: GitRepo) -> WorkOption {
-        let statuses = match repo.statuses() {
+        let statuses = match repo.statuses(&self.options) {
             Err(_) => return None,
-            Ok(ref s) if s.is_empty() => return None,
             Ok(s) => s,
         };
 
-        let mut result = Vec::new();
+        let head = repo.head_state().ok();
 
-        for status_entry in statuses.iter() {
-            for (path, status) in status_entry.iter() {
-                result.push((path.to_owned(), status));
-            }
+        let is_busy = head.as_ref().is_some_and(|x| x.operation().is_some());
+        let is_unpushed = head.as_ref().is_some_and(|x| x.unpushed() > 0);
+
+        if self.dirty_only && statuses.is_empty() {
+            return None;
+        }
+
+        if self.format == StatusFormat::Short {
+            return Some(Box::new(StatusShortResult::new(
+                repo.path().into(),
+                head,
+                &statuses,
+            )));
+        }
+
+        if statuses.is_empty() && !is_busy && !(self.all && is_unpushed) {
+            return None;
+        }
+
+        if self.format == StatusFormat::Porcelain {
+            return Some(Box::new(StatusPorcelainResult::new(&repo, &statuses)));
         }
 
         let result = Box::new(StatusCommandResult {
             path: repo.path().into(),
-            statuses: result,
+            statuses: statuses.iter().map(StatusLine::new).collect(),
+            head,
         });
 
         Some(result)
     }
 }
 
+fn print_head(printer: &mut ColorPrinter<'_>, cs: &mut ColorSpec, head: &HeadState) {
+    cs.set_fg(Some(Color::Cyan));
+
+    printer.color_context(cs, |h| {
+        match (head.branch(), head.detached_at(), head.upstream()) {
+            (_, Some(id), _) => write!(h, " HEAD detached at {}", id),
+            (Some(branch), _, Some(upstream)) => write!(h, " {}...{}", branch, upstream),
+            (Some(branch), _, None) => write!(h, " {}", branch),
+            (None, None, _) => write!(h, " HEAD"),
+        }
+        .expect("write fail")
+    });
+
+    let tracking = match (head.ahead(), head.behind()) {
+        _ if head.is_upstream_gone() => Some("[upstream gone]".to_string()),
+        _ if head.upstream().is_none() && head.unpushed() > 0 => {
+            Some(format!("[never pushed, {} commit(s)]", head.unpushed()))
+        }
+        (0, 0) => None,
+        (ahead, 0) => Some(format!("[ahead {}]", ahead)),
+        (0, behind) => Some(format!("[behind {}]", behind)),
+        (ahead, behind) => Some(format!("[ahead {}, behind {}]", ahead, behind)),
+    };
+
+    if let Some(tracking) = tracking {
+        cs.set_fg(Some(Color::Yellow));
+        printer.color_context(cs, |h| write!(h, " {}", tracking).expect("write fail"));
+    }
+
+    let operation = match head.operation() {
+        Some(Operation::Merge) => "MERGING",
+        Some(Operation::Rebase) => "REBASING",
+        Some(Operation::CherryPick) => "CHERRY-PICKING",
+        Some(Operation::Revert) => "REVERTING",
+        Some(Operation::Bisect) => "BISECTING",
+        Some(Operation::ApplyMailbox) => "APPLYING PATCHES",
+        None => return,
+    };
+
+    cs.set_fg(Some(Color::Red));
+    printer.color_context(cs, |h| write!(h, " {}", operation).expect("write fail"));
+}
+
 impl WorkResult for StatusCommandResult {
     fn print(&self, printer: &mut ColorPrinter<'_>) {
-        writeln!(printer, "{}", self.path.display()).expect("write fail");
+        write!(printer, "{}", self.path.display()).expect("write fail");
 
         let mut cs = ColorSpec::new();
         cs.set_intense(true);
 
-        for (path, status) in &self.statuses {
-            let (status_str, color) = match status {
-                Status::Conflicted => ("       Conflicted", Color::Magenta),
-                Status::Deleted => ("          Deleted", Color::Red),
-                Status::Ignored => ("          Ignored", Color::Magenta),
-                Status::Modified => ("         Modified", Color::Cyan),
-                Status::New => ("              New", Color::Green),
-                Status::Renamed => ("          Renamed", Color::Green),
-                Status::StagedDeleted => ("   Staged Deleted", Color::Magenta),
-                Status::StagedModified => ("  Staged Modified", Color::Magenta),
-                Status::StagedNew => ("       Staged New", Color::Magenta),
-                Status::StagedRenamed => ("   Staged Renamed", Color::Magenta),
-                Status::StagedTypechange => ("Staged Typechange", Color::Magenta),
-                Status::Typechange => ("       Typechange", Color::Cyan),
-                Status::Unknown => ("          Unknown", Color::Magenta),
-            };
-
-            cs.set_fg(Some(color));
+        if let Some(ref head) = self.head {
+            write!(printer, " -").expect("write fail");
+            print_head(printer, &mut cs, head);
+        }
+
+        writeln!(printer).expect("write fail");
+
+        for line in &self.statuses {
+            let (worktree, worktree_color) = line.worktree_label();
+
+            cs.set_fg(Some(Color::Magenta));
+            printer.color_context(&cs, |h| {
+                write!(h, " {:>width$}", line.staged_label(), width = STAGED_WIDTH)
+                    .expect("write fail")
+            });
 
+            cs.set_fg(Some(worktree_color));
             printer.color_context(&cs, |h| {
-                write!(h, " {}", status_str).expect("write fail");
+                write!(h, " {:width$}", worktree, width = WORKTREE_WIDTH).expect("write fail")
             });
 
-            writeln!(printer, " {}", path.display()).expect("write fail");
+            match line.old_path {
+                Some(ref old_path) => writeln!(
+                    printer,
+                    " {} -> {}",
+                    old_path.display(),
+                    line.path.display()
+                ),
+                None => writeln!(printer, " {}", line.path.display()),
+            }
+            .expect("write fail");
+        }
+    }
+}
+
+impl StatusShortResult {
+    /// Files are counted once per kind of change, so a file that is staged
+    /// and then modified again counts as both.
+    fn new(path: PathBuf, head: Option<HeadState>, statuses: &Statuses) -> Self {
+        let mut result = Self {
+            head,
+            path,
+            staged: 0,
+            modified: 0,
+            untracked: 0,
+            conflicted: 0,
+        };
+
+        for entry in statuses.iter() {
+            let worktree = entry.worktree_status();
+
+            result.staged += entry.index_status().is_some() as usize;
+            result.modified += worktree.is_some_and(|x| x != Change::New) as usize;
+            result.untracked += (worktree == Some(Change::New)) as usize;
+            result.conflicted += entry.is_conflicted() as usize;
+        }
+
+        result
+    }
+
+    fn color(&self) -> Option<Color> {
+        let head = self.head.as_ref();
+        let is_busy = head.is_some_and(|x| x.operation().is_some());
+        let is_unpushed = head.is_some_and(|x| x.unpushed() > 0 || x.ahead() > 0);
+
+        if self.conflicted > 0 || is_busy {
+            Some(Color::Red)
+        } else if self.staged > 0 || self.modified > 0 {
+            Some(Color::Yellow)
+        } else if self.untracked > 0 || is_unpushed {
+            Some(Color::Cyan)
+        } else {
+            None
         }
     }
 }
+
+fn short_branch(head: Option<&HeadState>) -> String {
+    match head.map(|x| (x.branch(), x.detached_at())) {
+        Some((_, Some(id))) => format!("({})", id),
+        Some((Some(branch), _)) => branch.into(),
+        _ => "HEAD".into(),
+    }
+}
+
+fn short_tracking(head: Option<&HeadState>) -> String {
+    let head = match head {
+        Some(head) => head,
+        None => return String::new(),
+    };
+
+    if head.is_upstream_gone() {
+        return "upstream gone".into();
+    }
+
+    if head.upstream().is_none() {
+        return match head.unpushed() {
+            0 => String::new(),
+            unpushed => format!("unpushed {}", unpushed),
+        };
+    }
+
+    match (head.ahead(), head.behind()) {
+        (0, 0) => String::new(),
+        (ahead, 0) => format!("ahead {}", ahead),
+        (0, behind) => format!("behind {}", behind),
+        (ahead, behind) => format!("ahead {} behind {}", ahead, behind),
+    }
+}
+
+impl WorkResult for StatusShortResult {
+    fn print(&self, printer: &mut ColorPrinter<'_>) {
+        let head = self.head.as_ref();
+
+        let mut cs = ColorSpec::new();
+        cs.set_intense(true);
+        cs.set_fg(self.color());
+
+        printer.color_context(&cs, |h| {
+            write!(
+                h,
+                " {:branch$} {:tracking$} S:{:<3} M:{:<3} U:{:<3} C:{:<3}",
+                short_branch(head),
+                short_tracking(head),
+                self.staged,
+                self.modified,
+                self.untracked,
+                self.conflicted,
+                branch = BRANCH_WIDTH,
+                tracking = TRACKING_WIDTH,
+            )
+            .expect("write fail")
+        });
+
+        writeln!(printer, " - {}", self.path.display()).expect("write fail");
+    }
+}
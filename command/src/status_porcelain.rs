@@ -0,0 +1,319 @@
+use crate::WorkResult;
+use color_printer::ColorPrinter;
+use gitlib::{FileVersion, GitRepo, StatusEntry, Statuses};
+use std::{io::Write, path::PathBuf};
+
+const NO_ID: &str = "0000000000000000000000000000000000000000";
+
+/// `git status --porcelain=v2` records for one repo, after a `# repo.path`
+/// header line
+pub(crate) struct StatusPorcelainResult {
+    path: PathBuf,
+    records: Vec<String>,
+}
+
+impl StatusPorcelainResult {
+    pub(crate) fn new(repo: &GitRepo, statuses: &Statuses) -> Self {
+        let mut records = statuses
+            .iter()
+            .flat_map(|x| records(repo, x))
+            .collect::<Vec<_>>();
+
+        // Git lists changed and unmerged entries together in path order, then
+        // untracked and ignored.
+        records.sort_by(|(a_path, a), (b_path, b)| {
+            let group = |x: &str| match x.as_bytes()[0] {
+                b'?' => 1,
+                b'!' => 2,
+                _ => 0,
+            };
+
+            (group(a), a_path).cmp(&(group(b), b_path))
+        });
+
+        Self {
+            path: repo.path().into(),
+            records: records.into_iter().map(|(_, x)| x).collect(),
+        }
+    }
+}
+
+fn mode(version: Option<&FileVersion>) -> String {
+    format!("{:06o}", version.map_or(0, |x| x.mode()))
+}
+
+fn id(version: Option<&FileVersion>) -> &str {
+    version.map_or(NO_ID, |x| x.id())
+}
+
+/// Porcelain lines for one entry, each with the path git sorts it by. Most
+/// entries are one line, but an inexact rename is split in two.
+fn records(repo: &GitRepo, entry: &StatusEntry) -> Vec<(PathBuf, String)> {
+    let path = entry.path();
+    let line = |x: String| vec![(path.to_owned(), x)];
+
+    let (x, y) = match (entry.index_code(), entry.worktree_code()) {
+        ('?', _) | (_, '?') => return line(format!("? {}", path.display())),
+        ('!', _) | (_, '!') => return line(format!("! {}", path.display())),
+        ('U', _) | (_, 'U') => return line(unmerged(repo, entry)),
+        codes => codes,
+    };
+
+    // Sides that didn't change are the same as the index.
+    let index = entry.index();
+    let head = if x == '.' { index } else { entry.head() };
+    let worktree = if y == '.' { index } else { entry.worktree() };
+
+    let submodule = match [head, index, worktree].iter().flatten().next() {
+        Some(version) if version.is_submodule() => {
+            let is_moved = id(head) != id(index) || (y != '.' && id(index) != id(worktree));
+            if is_moved {
+                "SC.."
+            } else {
+                "S..."
+            }
+        }
+        _ => "N...",
+    };
+
+    let fields = |x: char, y: char, versions: [Option<&FileVersion>; 3]| {
+        let [head, index, worktree] = versions;
+
+        format!(
+            "{}{} {} {} {} {} {} {}",
+            x,
+            y,
+            submodule,
+            mode(head),
+            mode(index),
+            mode(worktree),
+            id(head),
+            id(index)
+        )
+    };
+
+    let old_path = match entry.old_path() {
+        Some(old_path) => old_path,
+        None => {
+            let fields = fields(x, y, [head, index, worktree]);
+            return line(format!("1 {} {}", fields, path.display()));
+        }
+    };
+
+    let (from, to) = if x == 'R' {
+        (head, index)
+    } else {
+        (index, worktree)
+    };
+
+    // libgit2 doesn't report how similar an inexact rename is, so only exact
+    // renames get a `R100` record. Others are listed as a deletion and an
+    // addition, as git does without rename detection.
+    if id(from) == id(to) {
+        let fields = fields(x, y, [head, index, worktree]);
+        let record = format!(
+            "2 {} R100 {}\t{}",
+            fields,
+            path.display(),
+            old_path.display()
+        );
+
+        return line(record);
+    }
+
+    let (deleted, added) = if x == 'R' {
+        let deleted = fields('D', '.', [head, None, None]);
+        let added = fields('A', y, [None, index, worktree]);
+
+        (deleted, format!("1 {} {}", added, path.display()))
+    } else {
+        let deleted = fields(x, 'D', [head, index, None]);
+
+        (deleted, format!("? {}", path.display()))
+    };
+
+    vec![
+        (
+            old_path.to_owned(),
+            format!("1 {} {}", deleted, old_path.display()),
+        ),
+        (path.to_owned(), added),
+    ]
+}
+
+fn unmerged(repo: &GitRepo, entry: &StatusEntry) -> String {
+    let conflict = repo.conflict(entry.path()).ok().flatten();
+    let ancestor = conflict.as_ref().and_then(|x| x.ancestor());
+    let ours = conflict.as_ref().and_then(|x| x.ours());
+    let theirs = conflict.as_ref().and_then(|x| x.theirs());
+
+    let codes = match (ancestor.is_some(), ours.is_some(), theirs.is_some()) {
+        (true, false, false) => "DD",
+        (false, true, false) => "AU",
+        (true, true, false) => "UD",
+        (false, false, true) => "UA",
+        (true, false, true) => "DU",
+        (false, true, true) => "AA",
+        _ => "UU",
+    };
+
+    format!(
+        "u {} N... {} {} {} {} {} {} {} {}",
+        codes,
+        mode(ancestor),
+        mode(ours),
+        mode(theirs),
+        mode(entry.worktree().or(ours)),
+        id(ancestor),
+        id(ours),
+        id(theirs),
+        entry.path().display()
+    )
+}
+
+impl WorkResult for StatusPorcelainResult {
+    fn print(&self, printer: &mut ColorPrinter<'_>) {
+        writeln!(printer, "# repo.path {}", self.path.display()).expect("write fail");
+
+        for record in &self.records {
+            writeln!(printer, "{}", record).expect("write fail");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{commit_all, init};
+    use gitlib::StatusOptions;
+    use std::{fs, path::Path};
+    use tempfile::TempDir;
+
+    fn blob_id(content: &str) -> String {
+        git2::Oid::hash_object(git2::ObjectType::Blob, content.as_bytes())
+            .unwrap()
+            .to_string()
+    }
+
+    fn records(path: &Path) -> Vec<String> {
+        let repo = GitRepo::open(path).unwrap();
+        let options = StatusOptions::new().ignored(true).renames(true);
+        let statuses = repo.statuses(&options).unwrap();
+
+        StatusPorcelainResult::new(&repo, &statuses).records
+    }
+
+    #[test]
+    fn changed_renamed_untracked_and_ignored_records() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join(".gitignore"), "ignored.txt\n").unwrap();
+        fs::write(dir.path().join("a.txt"), "a\n").unwrap();
+        fs::write(dir.path().join("b.txt"), "b\n").unwrap();
+        let repo = init(dir.path());
+
+        fs::write(dir.path().join("a.txt"), "changed\n").unwrap();
+        fs::rename(dir.path().join("b.txt"), dir.path().join("c.txt")).unwrap();
+        let mut index = repo.index().unwrap();
+        index.remove_path(Path::new("b.txt")).unwrap();
+        index.add_path(Path::new("c.txt")).unwrap();
+        index.write().unwrap();
+        fs::write(dir.path().join("ignored.txt"), "ignored\n").unwrap();
+        fs::write(dir.path().join("new.txt"), "new\n").unwrap();
+
+        let (a, b) = (blob_id("a\n"), blob_id("b\n"));
+
+        assert_eq!(
+            records(dir.path()),
+            vec![
+                format!("1 .M N... 100644 100644 100644 {} {} a.txt", a, a),
+                format!(
+                    "2 R. N... 100644 100644 100644 {} {} R100 c.txt\tb.txt",
+                    b, b
+                ),
+                "? new.txt".to_string(),
+                "! ignored.txt".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn unmerged_records_keep_path_order() {
+        let dir = TempDir::new().unwrap();
+        for name in &["a.txt", "b.txt", "c.txt"] {
+            fs::write(dir.path().join(name), "base\n").unwrap();
+        }
+        let repo = init(dir.path());
+        let base = repo.head().unwrap().target().unwrap();
+
+        fs::write(dir.path().join("b.txt"), "theirs\n").unwrap();
+        let theirs = commit_all(&repo, "theirs");
+
+        let base = repo.find_object(base, None).unwrap();
+        repo.reset(&base, git2::ResetType::Hard, None).unwrap();
+        fs::write(dir.path().join("b.txt"), "ours\n").unwrap();
+        commit_all(&repo, "ours");
+
+        let theirs = repo.find_annotated_commit(theirs).unwrap();
+        repo.merge(&[&theirs], None, None).unwrap();
+
+        fs::write(dir.path().join("a.txt"), "changed\n").unwrap();
+        fs::write(dir.path().join("c.txt"), "changed\n").unwrap();
+        fs::write(dir.path().join("0.txt"), "new\n").unwrap();
+
+        let id = blob_id("base\n");
+
+        assert_eq!(
+            records(dir.path()),
+            vec![
+                format!("1 .M N... 100644 100644 100644 {} {} a.txt", id, id),
+                format!(
+                    "u UU N... 100644 100644 100644 100644 {} {} {} b.txt",
+                    id,
+                    blob_id("ours\n"),
+                    blob_id("theirs\n")
+                ),
+                format!("1 .M N... 100644 100644 100644 {} {} c.txt", id, id),
+                "? 0.txt".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn edited_renames_are_a_deletion_and_an_addition() {
+        let dir = TempDir::new().unwrap();
+        let lines = (1..=10)
+            .map(|x| format!("line {}\n", x))
+            .collect::<String>();
+        fs::write(dir.path().join("old.txt"), &lines).unwrap();
+        let repo = init(dir.path());
+
+        let edited = lines.replace("line 5", "edited");
+        fs::remove_file(dir.path().join("old.txt")).unwrap();
+        fs::write(dir.path().join("new.txt"), &edited).unwrap();
+        let mut index = repo.index().unwrap();
+        index.remove_path(Path::new("old.txt")).unwrap();
+        index.add_path(Path::new("new.txt")).unwrap();
+        index.write().unwrap();
+
+        let repo = GitRepo::open(dir.path()).unwrap();
+        let statuses = repo.statuses(&StatusOptions::new().renames(true)).unwrap();
+        let entry = statuses.iter().next().unwrap();
+        assert_eq!(entry.old_path(), Some(Path::new("old.txt")));
+
+        assert_eq!(
+            records(dir.path()),
+            vec![
+                format!(
+                    "1 A. N... 000000 100644 100644 {} {} new.txt",
+                    NO_ID,
+                    blob_id(&edited)
+                ),
+                format!(
+                    "1 D. N... 100644 000000 000000 {} {} old.txt",
+                    blob_id(&lines),
+                    NO_ID
+                ),
+            ]
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use color_printer::ColorPrinter;
-use gitlib::GitRepo;
+use gitlib::RepoHandle;
 use std::{marker::Send, sync::mpsc::Sender};
 
 pub trait WorkResult: Send {
@@ -9,7 +9,7 @@ pub trait WorkResult: Send {
 pub enum WorkType {
     Repo {
         index: usize,
-        repo: GitRepo,
+        repo: RepoHandle,
         tx: Sender<WorkType>,
     },
     Work {
@@ -19,6 +19,11 @@ pub enum WorkType {
     WorkEmpty {
         index: usize,
     },
+    Plan {
+        index: usize,
+        repo: RepoHandle,
+        result: Box<dyn WorkResult>,
+    },
 }
 
 impl WorkType {
@@ -30,7 +35,15 @@ impl WorkType {
         WorkType::WorkEmpty { index }
     }
 
-    pub fn repo(index: usize, repo: GitRepo, tx: Sender<WorkType>) -> Self {
+    pub fn repo(index: usize, repo: RepoHandle, tx: Sender<WorkType>) -> Self {
         WorkType::Repo { index, repo, tx }
     }
+
+    pub fn plan(index: usize, repo: RepoHandle, result: Box<dyn WorkResult>) -> Self {
+        WorkType::Plan {
+            index,
+            repo,
+            result,
+        }
+    }
 }
@@ -0,0 +1,33 @@
+use crate::Error;
+
+#[derive(Debug, Clone)]
+pub struct Branch {
+    name: String,
+    is_remote: bool,
+    is_head: bool,
+}
+
+impl Branch {
+    pub(crate) fn from_branch(
+        branch: &git2::Branch<'_>,
+        branch_type: git2::BranchType,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            name: branch.name()?.map(|x| x.into()).ok_or(Error::InvalidUtf8)?,
+            is_remote: branch_type == git2::BranchType::Remote,
+            is_head: branch.is_head(),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_remote(&self) -> bool {
+        self.is_remote
+    }
+
+    pub fn is_head(&self) -> bool {
+        self.is_head
+    }
+}
@@ -0,0 +1,58 @@
+use std::time::SystemTime;
+
+#[derive(Debug, Clone)]
+pub struct BranchInfo {
+    pub(crate) name: String,
+    pub(crate) is_head: bool,
+    pub(crate) upstream: Option<String>,
+    pub(crate) upstream_gone: bool,
+    pub(crate) ahead: usize,
+    pub(crate) behind: usize,
+    pub(crate) author: String,
+    pub(crate) time: SystemTime,
+    pub(crate) is_merged: bool,
+}
+
+impl BranchInfo {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_head(&self) -> bool {
+        self.is_head
+    }
+
+    pub fn upstream(&self) -> Option<&str> {
+        self.upstream.as_deref()
+    }
+
+    /// The branch tracks an upstream that no longer exists
+    pub fn is_upstream_gone(&self) -> bool {
+        self.upstream_gone
+    }
+
+    /// Commits on the branch that are not on its upstream
+    pub fn ahead(&self) -> usize {
+        self.ahead
+    }
+
+    /// Commits on the upstream that are not on the branch
+    pub fn behind(&self) -> usize {
+        self.behind
+    }
+
+    /// Author of the tip commit
+    pub fn author(&self) -> &str {
+        &self.author
+    }
+
+    /// Commit time of the tip commit
+    pub fn time(&self) -> SystemTime {
+        self.time
+    }
+
+    /// Whether the tip is reachable from the default branch
+    pub fn is_merged(&self) -> bool {
+        self.is_merged
+    }
+}
@@ -0,0 +1,12 @@
+/// How a file differs between HEAD and the index, or the index and the work
+/// tree.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Change {
+    /// Added to the index, or untracked in the work tree
+    New,
+    Modified,
+    Deleted,
+    Renamed,
+    /// Changed between a file, a symlink and a submodule
+    Typechange,
+}
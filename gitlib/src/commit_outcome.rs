@@ -0,0 +1,24 @@
+/// What `GitRepo::commit` did, or would do.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommitOutcome {
+    pub(crate) id: Option<String>,
+    pub(crate) amended: Option<String>,
+    pub(crate) files: usize,
+}
+
+impl CommitOutcome {
+    /// The new commit, or `None` for a preview
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// Short id of the commit that was replaced, when amending
+    pub fn amended(&self) -> Option<&str> {
+        self.amended.as_deref()
+    }
+
+    /// Files that differ from the previous HEAD
+    pub fn files(&self) -> usize {
+        self.files
+    }
+}
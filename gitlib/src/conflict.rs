@@ -0,0 +1,24 @@
+use crate::FileVersion;
+
+/// The three sides of a conflicted file in the index. A missing side means
+/// the file was added or deleted there.
+#[derive(Debug, Clone)]
+pub struct Conflict {
+    pub(crate) ancestor: Option<FileVersion>,
+    pub(crate) ours: Option<FileVersion>,
+    pub(crate) theirs: Option<FileVersion>,
+}
+
+impl Conflict {
+    pub fn ancestor(&self) -> Option<&FileVersion> {
+        self.ancestor.as_ref()
+    }
+
+    pub fn ours(&self) -> Option<&FileVersion> {
+        self.ours.as_ref()
+    }
+
+    pub fn theirs(&self) -> Option<&FileVersion> {
+        self.theirs.as_ref()
+    }
+}
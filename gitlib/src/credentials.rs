@@ -1,3 +1,12 @@
+use crate::{network, Error};
+use std::{
+    collections::BTreeMap,
+    env,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+use url::Url;
+
 #[derive(Debug)]
 pub enum CredentialType {
     UserPassPlaintext,
@@ -83,3 +92,417 @@ impl Iterator for Credentials {
         }
     }
 }
+
+const DEFAULT_KEYS: [&str; 3] = ["id_ed25519", "id_ecdsa", "id_rsa"];
+const TOKEN_VAR: &str = "GITPLZ_TOKEN";
+const TOKEN_USERNAME_VAR: &str = "GITPLZ_USERNAME";
+const TOKEN_USERNAME: &str = "x-access-token";
+
+/// Username and password per HTTPS host, shared by every repo for the rest
+/// of the run so a credential helper is only asked once per host.
+static PLAINTEXT_CACHE: Mutex<BTreeMap<String, (String, String)>> = Mutex::new(BTreeMap::new());
+
+/// Settings from `~/.ssh/config` that apply to one host.
+#[derive(Debug, Default)]
+struct SshHost {
+    /// The real host to connect to when the URL uses a `Host` alias
+    host_name: Option<String>,
+    user: Option<String>,
+    identity_files: Vec<PathBuf>,
+}
+
+impl SshHost {
+    /// Entries match on their `Host` patterns or their `HostName`, since the
+    /// URL git hands us may already use the real host name. Like ssh, the
+    /// first value found for a key wins.
+    fn lookup(host_name: &str) -> Self {
+        match dirs::home_dir() {
+            Some(home) => Self::lookup_in(&home.join(".ssh").join("config"), host_name),
+            None => Self::default(),
+        }
+    }
+
+    fn lookup_in(config: &Path, host_name: &str) -> Self {
+        let mut ssh_host = Self::default();
+
+        let hosts = match config.to_str().map(ssh_config::parse) {
+            Some(Ok(hosts)) => hosts,
+            _ => return ssh_host,
+        };
+
+        for host in hosts {
+            let mut properties = Vec::new();
+            let mut is_match = host
+                .name
+                .split_whitespace()
+                .any(|pattern| wildcard_match(pattern, host_name));
+
+            for property in host.properties {
+                let key = property.key.to_lowercase();
+                let value = property.value.to_string();
+
+                if key == "hostname" && value.eq_ignore_ascii_case(host_name) {
+                    is_match = true;
+                }
+
+                properties.push((key, value));
+            }
+
+            if !is_match {
+                continue;
+            }
+
+            for (key, value) in properties {
+                match key.as_str() {
+                    "hostname" if ssh_host.host_name.is_none() => ssh_host.host_name = Some(value),
+                    "user" if ssh_host.user.is_none() => ssh_host.user = Some(value),
+                    "identityfile" => ssh_host.identity_files.push(expand_home(&value)),
+                    _ => {}
+                }
+            }
+        }
+
+        ssh_host
+    }
+}
+
+/// Answers libgit2's credential requests for one fetch or push. Each call
+/// offers the next untried credential, so a rejected key moves on instead of
+/// being offered forever.
+#[derive(Default)]
+pub(crate) struct CredentialHandler {
+    config: Option<git2::Config>,
+    ssh_host: Option<SshHost>,
+    tried_agent: bool,
+    tried_keys: usize,
+    plaintext_step: usize,
+    offered_plaintext: bool,
+    failure: Option<String>,
+}
+
+impl CredentialHandler {
+    /// `config` is the repo's git config, used to find `credential.helper`.
+    pub fn new(config: Option<git2::Config>) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    pub fn credentials(
+        &mut self,
+        url: &str,
+        username_from_url: Option<&str>,
+        allowed: git2::CredentialType,
+    ) -> Result<git2::Cred, git2::Error> {
+        let host_name = host_from_url(url).unwrap_or_default();
+
+        if self.ssh_host.is_none() {
+            self.ssh_host = Some(SshHost::lookup(&host_name));
+        }
+
+        let username = username_from_url
+            .map(|x| x.to_string())
+            .or_else(|| self.ssh_host.as_ref().and_then(|x| x.user.clone()))
+            .or_else(|| env::var("USER").ok())
+            .or_else(|| env::var("USERNAME").ok())
+            .unwrap_or_else(|| "git".into());
+
+        for credential_type in Credentials::from(allowed) {
+            match credential_type {
+                CredentialType::Username => return git2::Cred::username(&username),
+                CredentialType::UserPassPlaintext => {
+                    if let Some(cred) = self.next_plaintext(url, &host_name, username_from_url) {
+                        return cred;
+                    }
+
+                    return self.fail(format!(
+                        "No credentials were accepted for {}. Configure credential.helper or set {}",
+                        host_name, TOKEN_VAR
+                    ));
+                }
+                CredentialType::SshKey => {
+                    if let Some(cred) = self.next_ssh_key(&username) {
+                        return cred;
+                    }
+
+                    return self.fail(format!(
+                        "No SSH key was accepted for {}@{}. Tried ssh-agent and {} key file(s)",
+                        username, host_name, self.tried_keys
+                    ));
+                }
+                _ => continue,
+            }
+        }
+
+        self.fail(format!(
+            "No supported credentials for {} (allowed: {:?})",
+            url,
+            Credentials::from(allowed).collect::<Vec<_>>()
+        ))
+    }
+
+    /// Map a failed fetch or push to `Error::Auth` when credentials were the cause.
+    pub fn error_for(&self, error: git2::Error) -> Error {
+        match self.failure {
+            Some(ref message) => Error::Auth(message.clone()),
+            None => network::remote_error(error),
+        }
+    }
+
+    fn next_ssh_key(&mut self, username: &str) -> Option<Result<git2::Cred, git2::Error>> {
+        if !self.tried_agent {
+            self.tried_agent = true;
+            return Some(git2::Cred::ssh_key_from_agent(username));
+        }
+
+        let mut keys = self
+            .ssh_host
+            .as_ref()
+            .map(|x| x.identity_files.clone())
+            .unwrap_or_default();
+
+        if let Some(ssh_dir) = dirs::home_dir().map(|x| x.join(".ssh")) {
+            keys.extend(DEFAULT_KEYS.iter().map(|x| ssh_dir.join(x)));
+        }
+
+        let private_key = keys
+            .into_iter()
+            .filter(|x| x.is_file())
+            .nth(self.tried_keys)?;
+        let public_key = PathBuf::from(format!("{}.pub", private_key.display()));
+
+        self.tried_keys += 1;
+
+        Some(git2::Cred::ssh_key(
+            username,
+            Some(&public_key)
+                .filter(|x| x.is_file())
+                .map(|x| x.as_path()),
+            &private_key,
+            None,
+        ))
+    }
+
+    /// Offer, in order, what an earlier repo cached for this host, then git's
+    /// `credential.helper`, then a token from the environment.
+    fn next_plaintext(
+        &mut self,
+        url: &str,
+        host_name: &str,
+        username_from_url: Option<&str>,
+    ) -> Option<Result<git2::Cred, git2::Error>> {
+        // Held while the helper runs, so parallel repos on one host wait for
+        // the first answer instead of all asking the helper at once.
+        let mut cache = PLAINTEXT_CACHE
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        // Being asked again means the last offer was rejected.
+        if self.offered_plaintext {
+            cache.remove(host_name);
+        }
+
+        while self.plaintext_step < 3 {
+            self.plaintext_step += 1;
+
+            let user_pass = match self.plaintext_step {
+                1 => cache.get(host_name).cloned(),
+                2 => {
+                    let user_pass = self.config.as_ref().and_then(|config| {
+                        git2::CredentialHelper::new(url)
+                            .config(config)
+                            .username(username_from_url)
+                            .execute()
+                    });
+
+                    if let Some(ref user_pass) = user_pass {
+                        cache.insert(host_name.into(), user_pass.clone());
+                    }
+
+                    user_pass
+                }
+                _ => env::var(TOKEN_VAR).ok().map(|token| {
+                    let username = username_from_url
+                        .map(|x| x.to_string())
+                        .or_else(|| env::var(TOKEN_USERNAME_VAR).ok())
+                        .unwrap_or_else(|| TOKEN_USERNAME.into());
+
+                    (username, token)
+                }),
+            };
+
+            if let Some((username, password)) = user_pass {
+                self.offered_plaintext = true;
+                return Some(git2::Cred::userpass_plaintext(&username, &password));
+            }
+        }
+
+        None
+    }
+
+    fn fail(&mut self, message: String) -> Result<git2::Cred, git2::Error> {
+        let error = git2::Error::from_str(&message);
+        self.failure = Some(message);
+
+        Err(error)
+    }
+}
+
+/// Host of either a real URL or scp-like syntax such as `git@host:org/repo.git`.
+/// `file://` URLs and local paths have no host.
+pub(crate) fn host_from_url(url: &str) -> Option<String> {
+    if let Ok(parsed) = Url::parse(url) {
+        if parsed.scheme() == "file" {
+            return None;
+        }
+
+        if let Some(host) = parsed.host_str() {
+            return Some(host.into());
+        }
+    }
+
+    // Like git, only treat it as scp-like when a colon comes before any slash
+    let host = url.split(':').next()?;
+
+    if host.len() == url.len() || host.contains('/') {
+        return None;
+    }
+
+    host.rsplit('@').next().map(|x| x.into())
+}
+
+/// `url` pointed at the `HostName` of its `~/.ssh/config` entry, for SSH
+/// remotes that use a `Host` alias. `None` when the URL already has the real
+/// host. libgit2 doesn't read ssh config, so it would look the alias up in DNS.
+pub(crate) fn ssh_config_url(url: &str) -> Option<String> {
+    let host = host_from_url(url)?;
+    let host_name = SshHost::lookup(&host).host_name?;
+
+    if host_name.eq_ignore_ascii_case(&host) {
+        return None;
+    }
+
+    replace_host(url, &host_name)
+}
+
+/// Swap the host of an `ssh://` URL or of scp-like syntax. Other URLs are
+/// left alone, since ssh config only applies to SSH.
+fn replace_host(url: &str, host_name: &str) -> Option<String> {
+    if let Ok(mut parsed) = Url::parse(url) {
+        if parsed.host_str().is_some() {
+            if parsed.scheme() != "ssh" {
+                return None;
+            }
+
+            parsed.set_host(Some(host_name)).ok()?;
+
+            return Some(parsed.into());
+        }
+    }
+
+    let (user_host, path) = url.split_once(':')?;
+    let user = match user_host.rsplit_once('@') {
+        Some((user, _)) => format!("{}@", user),
+        None => String::new(),
+    };
+
+    Some(format!("{}{}:{}", user, host_name, path))
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.starts_with("~/"), dirs::home_dir()) {
+        (true, Some(home)) => home.join(&path[2..]),
+        _ => PathBuf::from(path),
+    }
+}
+
+/// ssh_config style patterns, where `*` matches any run of characters and
+/// `?` matches exactly one.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.to_lowercase().chars().collect::<Vec<_>>();
+    let text = text.to_lowercase().chars().collect::<Vec<_>>();
+
+    fn matches(pattern: &[char], text: &[char]) -> bool {
+        match (pattern.first(), text.first()) {
+            (None, None) => true,
+            (Some('*'), _) => {
+                matches(&pattern[1..], text) || (!text.is_empty() && matches(pattern, &text[1..]))
+            }
+            (Some('?'), Some(_)) => matches(&pattern[1..], &text[1..]),
+            (Some(p), Some(t)) if p == t => matches(&pattern[1..], &text[1..]),
+            _ => false,
+        }
+    }
+
+    matches(&pattern, &text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_from_ssh_and_scp_urls() {
+        assert_eq!(
+            host_from_url("ssh://git@example.com:22/org/repo.git").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(
+            host_from_url("git@example.com:org/repo.git").as_deref(),
+            Some("example.com")
+        );
+    }
+
+    #[test]
+    fn no_host_for_file_urls() {
+        assert_eq!(host_from_url("file:///srv/git/repo.git"), None);
+        assert_eq!(host_from_url("file://localhost/srv/git/repo.git"), None);
+    }
+
+    #[test]
+    fn no_host_for_local_paths() {
+        assert_eq!(host_from_url("/srv/git/repo.git"), None);
+        assert_eq!(host_from_url("../repo.git"), None);
+        assert_eq!(host_from_url("./dir:with/colon.git"), None);
+    }
+
+    #[test]
+    fn host_name_replaces_ssh_and_scp_hosts() {
+        assert_eq!(
+            replace_host("ssh://git@alias:2222/org/repo.git", "example.com").as_deref(),
+            Some("ssh://git@example.com:2222/org/repo.git")
+        );
+        assert_eq!(
+            replace_host("git@alias:org/repo.git", "example.com").as_deref(),
+            Some("git@example.com:org/repo.git")
+        );
+        assert_eq!(
+            replace_host("https://alias/org/repo.git", "example.com"),
+            None
+        );
+    }
+
+    #[test]
+    fn host_name_is_read_for_aliases() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = dir.path().join("config");
+        std::fs::write(
+            &config,
+            "Host alias\n  HostName example.com\n  User deploy\n",
+        )
+        .unwrap();
+
+        let ssh_host = SshHost::lookup_in(&config, "alias");
+        assert_eq!(ssh_host.host_name.as_deref(), Some("example.com"));
+        assert_eq!(ssh_host.user.as_deref(), Some("deploy"));
+        assert!(SshHost::lookup_in(&config, "other").host_name.is_none());
+    }
+
+    #[test]
+    fn wildcard_patterns() {
+        assert!(wildcard_match("*.example.com", "git.example.com"));
+        assert!(wildcard_match("git?", "GIT1"));
+        assert!(!wildcard_match("*.example.com", "example.org"));
+    }
+}
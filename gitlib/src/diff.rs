@@ -0,0 +1,135 @@
+use crate::{Change, Error};
+use std::path::{Path, PathBuf};
+
+/// Which two sides `GitRepo::diff` compares
+#[derive(Debug, Clone)]
+pub enum DiffRange {
+    /// Unstaged changes, from the index to the work tree
+    Worktree,
+    /// Staged changes, from HEAD to the index
+    Index,
+    /// From one revision to another, as in `git diff <from> <to>`
+    Revs(String, String),
+}
+
+/// One changed file, with its line counts and, when asked for, its patch
+#[derive(Debug, Clone)]
+pub struct FileDiff {
+    path: PathBuf,
+    old_path: Option<PathBuf>,
+    change: Change,
+    insertions: usize,
+    deletions: usize,
+    binary: bool,
+    patch: Option<String>,
+}
+
+impl FileDiff {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Where a renamed file used to be
+    pub fn old_path(&self) -> Option<&Path> {
+        self.old_path.as_deref()
+    }
+
+    pub fn change(&self) -> Change {
+        self.change
+    }
+
+    pub fn insertions(&self) -> usize {
+        self.insertions
+    }
+
+    pub fn deletions(&self) -> usize {
+        self.deletions
+    }
+
+    /// Binary files have no line counts
+    pub fn is_binary(&self) -> bool {
+        self.binary
+    }
+
+    /// Unified diff text, including the `diff --git` header
+    pub fn patch(&self) -> Option<&str> {
+        self.patch.as_deref()
+    }
+}
+
+/// Every file changed between the two sides of a `DiffRange`
+#[derive(Debug, Clone, Default)]
+pub struct Diff {
+    files: Vec<FileDiff>,
+}
+
+impl Diff {
+    /// Renames are detected the way `git diff` does by default.
+    pub(crate) fn new(mut diff: git2::Diff<'_>, with_patch: bool) -> Result<Self, Error> {
+        diff.find_similar(None)?;
+
+        let mut files = Vec::new();
+
+        for (index, delta) in diff.deltas().enumerate() {
+            let change = match delta.status() {
+                git2::Delta::Added | git2::Delta::Copied | git2::Delta::Untracked => Change::New,
+                git2::Delta::Modified => Change::Modified,
+                git2::Delta::Deleted => Change::Deleted,
+                git2::Delta::Renamed => Change::Renamed,
+                git2::Delta::Typechange => Change::Typechange,
+                _ => continue,
+            };
+
+            let path = match delta.new_file().path().or_else(|| delta.old_file().path()) {
+                Some(path) => path.to_owned(),
+                None => continue,
+            };
+
+            let mut file = FileDiff {
+                path,
+                old_path: None,
+                change,
+                insertions: 0,
+                deletions: 0,
+                binary: delta.flags().is_binary(),
+                patch: None,
+            };
+
+            if change == Change::Renamed {
+                file.old_path = delta.old_file().path().map(|x| x.to_owned());
+            }
+
+            if let Some(mut patch) = git2::Patch::from_diff(&diff, index)? {
+                let (_, insertions, deletions) = patch.line_stats()?;
+
+                file.insertions = insertions;
+                file.deletions = deletions;
+                file.binary = patch.delta().flags().is_binary();
+
+                if with_patch {
+                    file.patch = Some(String::from_utf8_lossy(&patch.to_buf()?).into());
+                }
+            }
+
+            files.push(file);
+        }
+
+        Ok(Self { files })
+    }
+
+    pub fn files(&self) -> &[FileDiff] {
+        &self.files
+    }
+
+    pub fn insertions(&self) -> usize {
+        self.files.iter().map(|x| x.insertions).sum()
+    }
+
+    pub fn deletions(&self) -> usize {
+        self.files.iter().map(|x| x.deletions).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
@@ -0,0 +1,135 @@
+use crate::NetworkOptions;
+
+/// Options for `GitRepo::fetch`. Defaults match a plain `git fetch`.
+#[derive(Debug, Clone, Default)]
+pub struct FetchOptions {
+    prune: bool,
+    tags: Option<bool>,
+    network: NetworkOptions,
+}
+
+impl FetchOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Remove remote-tracking refs that no longer exist on the remote
+    pub fn prune(mut self, prune: bool) -> Self {
+        self.prune = prune;
+        self
+    }
+
+    /// `Some(true)` fetches every tag, `Some(false)` none, and `None` only
+    /// tags pointing at fetched commits.
+    pub fn tags(mut self, tags: Option<bool>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    pub fn network(mut self, network: NetworkOptions) -> Self {
+        self.network = network;
+        self
+    }
+
+    pub(crate) fn network_options(&self) -> &NetworkOptions {
+        &self.network
+    }
+
+    pub(crate) fn prune_option(&self) -> git2::FetchPrune {
+        if self.prune {
+            git2::FetchPrune::On
+        } else {
+            git2::FetchPrune::Unspecified
+        }
+    }
+
+    pub(crate) fn autotag_option(&self) -> git2::AutotagOption {
+        match self.tags {
+            Some(true) => git2::AutotagOption::All,
+            Some(false) => git2::AutotagOption::None,
+            None => git2::AutotagOption::Unspecified,
+        }
+    }
+}
+
+/// A ref changed by a fetch. A missing id means the ref was created or pruned.
+#[derive(Debug, Clone)]
+pub struct UpdatedRef {
+    name: String,
+    old_id: Option<String>,
+    new_id: Option<String>,
+}
+
+impl UpdatedRef {
+    pub(crate) fn new(name: &str, old_id: git2::Oid, new_id: git2::Oid) -> Self {
+        let id = |oid: git2::Oid| {
+            if oid.is_zero() {
+                None
+            } else {
+                Some(oid.to_string())
+            }
+        };
+
+        Self {
+            name: name.into(),
+            old_id: id(old_id),
+            new_id: id(new_id),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn old_id(&self) -> Option<&str> {
+        self.old_id.as_deref()
+    }
+
+    pub fn new_id(&self) -> Option<&str> {
+        self.new_id.as_deref()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FetchResult {
+    remote: String,
+    updated_refs: Vec<UpdatedRef>,
+    received_objects: usize,
+    received_bytes: usize,
+}
+
+impl FetchResult {
+    pub(crate) fn new(
+        remote: &str,
+        updated_refs: Vec<UpdatedRef>,
+        stats: &git2::Progress<'_>,
+    ) -> Self {
+        Self {
+            remote: remote.into(),
+            updated_refs,
+            received_objects: stats.received_objects(),
+            received_bytes: stats.received_bytes(),
+        }
+    }
+
+    /// Nothing was downloaded and no refs moved
+    pub fn is_up_to_date(&self) -> bool {
+        self.updated_refs.is_empty() && self.received_objects == 0
+    }
+
+    pub fn received_objects(&self) -> usize {
+        self.received_objects
+    }
+
+    pub fn received_bytes(&self) -> usize {
+        self.received_bytes
+    }
+
+    pub fn remote(&self) -> &str {
+        &self.remote
+    }
+
+    pub fn updated_refs(&self) -> &[UpdatedRef] {
+        &self.updated_refs
+    }
+}
@@ -0,0 +1,47 @@
+/// A file as it is in HEAD, the index or the work tree: its mode, like
+/// `0o100644`, and its blob id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileVersion {
+    mode: u32,
+    id: String,
+}
+
+impl FileVersion {
+    /// Sides of a diff where the file doesn't exist are `None`.
+    pub(crate) fn from_diff_file(file: &git2::DiffFile<'_>) -> Option<Self> {
+        let mode = match file.mode() {
+            git2::FileMode::Unreadable => return None,
+            git2::FileMode::Tree => 0o040000,
+            git2::FileMode::Blob => 0o100644,
+            git2::FileMode::BlobExecutable => 0o100755,
+            git2::FileMode::Link => 0o120000,
+            git2::FileMode::Commit => 0o160000,
+        };
+
+        Some(Self {
+            mode,
+            id: file.id().to_string(),
+        })
+    }
+
+    pub(crate) fn from_index_entry(entry: &git2::IndexEntry) -> Self {
+        Self {
+            mode: entry.mode,
+            id: entry.id.to_string(),
+        }
+    }
+
+    pub fn mode(&self) -> u32 {
+        self.mode
+    }
+
+    /// Blob id. Work tree files that were never hashed have an all-zero id.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The file is a submodule's checked out commit
+    pub fn is_submodule(&self) -> bool {
+        self.mode == 0o160000
+    }
+}
@@ -0,0 +1,85 @@
+/// An operation git stopped in the middle of, waiting for the user.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operation {
+    Merge,
+    Rebase,
+    CherryPick,
+    Revert,
+    Bisect,
+    ApplyMailbox,
+}
+
+impl Operation {
+    pub(crate) fn from_state(state: git2::RepositoryState) -> Option<Self> {
+        match state {
+            git2::RepositoryState::Clean => None,
+            git2::RepositoryState::Merge => Some(Operation::Merge),
+            git2::RepositoryState::Revert | git2::RepositoryState::RevertSequence => {
+                Some(Operation::Revert)
+            }
+            git2::RepositoryState::CherryPick | git2::RepositoryState::CherryPickSequence => {
+                Some(Operation::CherryPick)
+            }
+            git2::RepositoryState::Bisect => Some(Operation::Bisect),
+            git2::RepositoryState::ApplyMailbox => Some(Operation::ApplyMailbox),
+            git2::RepositoryState::Rebase
+            | git2::RepositoryState::RebaseInteractive
+            | git2::RepositoryState::RebaseMerge
+            | git2::RepositoryState::ApplyMailboxOrRebase => Some(Operation::Rebase),
+        }
+    }
+}
+
+/// Where HEAD is, and how it compares to what has been pushed.
+#[derive(Debug, Clone)]
+pub struct HeadState {
+    pub(crate) branch: Option<String>,
+    pub(crate) detached_at: Option<String>,
+    pub(crate) upstream: Option<String>,
+    pub(crate) upstream_gone: bool,
+    pub(crate) ahead: usize,
+    pub(crate) behind: usize,
+    pub(crate) unpushed: usize,
+    pub(crate) operation: Option<Operation>,
+}
+
+impl HeadState {
+    /// Checked out branch, or `None` when HEAD is detached
+    pub fn branch(&self) -> Option<&str> {
+        self.branch.as_deref()
+    }
+
+    /// Commit HEAD points at when it is detached
+    pub fn detached_at(&self) -> Option<&str> {
+        self.detached_at.as_deref()
+    }
+
+    pub fn upstream(&self) -> Option<&str> {
+        self.upstream.as_deref()
+    }
+
+    /// The branch tracks an upstream that no longer exists
+    pub fn is_upstream_gone(&self) -> bool {
+        self.upstream_gone
+    }
+
+    /// Commits on the branch that are not on its upstream
+    pub fn ahead(&self) -> usize {
+        self.ahead
+    }
+
+    /// Commits on the upstream that are not on the branch
+    pub fn behind(&self) -> usize {
+        self.behind
+    }
+
+    /// Commits that are on no remote-tracking branch at all. Repos without
+    /// remotes never count as unpushed.
+    pub fn unpushed(&self) -> usize {
+        self.unpushed
+    }
+
+    pub fn operation(&self) -> Option<Operation> {
+        self.operation
+    }
+}
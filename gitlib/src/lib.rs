@@ -14,7 +14,7 @@ pub enum Error {
     Conflict,
     Locked,
     Modified,
-    Auth,
+    Auth(String),
     Certificate,
     Applied,
     Peel,
@@ -24,6 +24,52 @@ pub enum Error {
     Directory,
     InvalidUtf8,
     ZeroSizedBranchName,
+    Rejected,
+    Network(String),
+    /// Talking to a remote failed in a way that retrying won't fix
+    Remote(String),
+    AlreadyPushed,
+    /// `user.name` or `user.email` is missing or empty
+    NoIdentity,
+}
+
+/// Short messages for printing next to a repo, rather than the `Debug` name
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            Error::Auth(message) => return write!(f, "authentication failed: {}", message),
+            Error::Network(message) => return write!(f, "network error: {}", message),
+            Error::Remote(message) => return f.write_str(message),
+            Error::GenericError => "git error",
+            Error::NotFound => "not found",
+            Error::Exists => "already exists",
+            Error::Ambiguous => "ambiguous reference",
+            Error::BufSize => "buffer too small",
+            Error::User => "cancelled",
+            Error::BareRepo => "bare repo",
+            Error::UnbornBranch => "no commits yet",
+            Error::Unmerged => "merge or rebase in progress",
+            Error::NotFastForward => "not a fast-forward",
+            Error::InvalidSpec => "invalid reference name",
+            Error::Conflict => "conflicts with local changes",
+            Error::Locked => "locked by another process",
+            Error::Modified => "changed on disk",
+            Error::Certificate => "invalid certificate",
+            Error::Applied => "already applied",
+            Error::Peel => "reference has the wrong type",
+            Error::Eof => "unexpected end of data",
+            Error::Invalid => "invalid operation",
+            Error::Uncommitted => "uncommitted changes",
+            Error::Directory => "is a directory",
+            Error::InvalidUtf8 => "name is not valid UTF-8",
+            Error::ZeroSizedBranchName => "empty branch name",
+            Error::Rejected => "remote rejected",
+            Error::AlreadyPushed => "already pushed",
+            Error::NoIdentity => "user.name or user.email is not set",
+        };
+
+        f.write_str(message)
+    }
 }
 
 impl From<git2::Error> for Error {
@@ -43,7 +89,7 @@ impl From<git2::Error> for Error {
             git2::ErrorCode::Conflict => Error::Conflict,
             git2::ErrorCode::Locked => Error::Locked,
             git2::ErrorCode::Modified => Error::Modified,
-            git2::ErrorCode::Auth => Error::Auth,
+            git2::ErrorCode::Auth => Error::Auth(error.message().into()),
             git2::ErrorCode::Certificate => Error::Certificate,
             git2::ErrorCode::Applied => Error::Applied,
             git2::ErrorCode::Peel => Error::Peel,
@@ -55,15 +101,60 @@ impl From<git2::Error> for Error {
     }
 }
 
+mod branch;
+pub use crate::branch::Branch;
+
+mod branch_info;
+pub use crate::branch_info::BranchInfo;
+
+mod change;
+pub use crate::change::Change;
+
+mod commit_outcome;
+pub use crate::commit_outcome::CommitOutcome;
+
+mod conflict;
+pub use crate::conflict::Conflict;
+
+mod diff;
+pub use crate::diff::{Diff, DiffRange, FileDiff};
+
+mod fetch;
+pub use crate::fetch::{FetchOptions, FetchResult, UpdatedRef};
+
+mod file_version;
+pub use crate::file_version::FileVersion;
+
+mod head_state;
+pub use crate::head_state::{HeadState, Operation};
+
+mod network;
+pub use crate::network::NetworkOptions;
+
+mod merge_outcome;
+pub use crate::merge_outcome::MergeOutcome;
+
+mod push_outcome;
+pub use crate::push_outcome::PushOutcome;
+
 mod reference;
 pub use crate::reference::Reference;
 
 mod repo;
 pub use crate::repo::GitRepo;
 
+mod repo_handle;
+pub use crate::repo_handle::RepoHandle;
+
+mod stash;
+pub use crate::stash::Stash;
+
 mod status_entry;
 pub use crate::status_entry::StatusEntry;
 
+mod status_options;
+pub use crate::status_options::StatusOptions;
+
 mod statuses;
 pub use crate::statuses::{StatusIter, Statuses};
 
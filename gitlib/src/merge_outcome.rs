@@ -0,0 +1,23 @@
+/// What `GitRepo::merge_upstream` did to the checked out branch.
+#[derive(Debug, Clone)]
+pub enum MergeOutcome {
+    UpToDate,
+    FastForwarded {
+        from: String,
+        to: String,
+    },
+    Rebased {
+        commits: usize,
+    },
+    /// Both sides have commits and rebasing wasn't requested
+    Diverged {
+        ahead: usize,
+        behind: usize,
+    },
+    /// Rebasing stopped on a conflict and was aborted
+    Conflict,
+    /// Tracked files have changes, or a merge or rebase is in progress
+    Dirty,
+    Detached,
+    NoUpstream,
+}
@@ -0,0 +1,208 @@
+use crate::{credentials, Error};
+use std::{
+    cell::Cell,
+    collections::BTreeMap,
+    sync::{Condvar, Mutex},
+    thread,
+    time::Duration,
+};
+
+/// Connections currently open per host, shared by every repo in the run.
+static OPEN_CONNECTIONS: Mutex<BTreeMap<String, usize>> = Mutex::new(BTreeMap::new());
+static CONNECTION_CLOSED: Condvar = Condvar::new();
+
+/// How fetches and pushes share remote hosts, and how often they retry
+/// transient failures like timeouts and reset connections.
+#[derive(Debug, Clone)]
+pub struct NetworkOptions {
+    connections_per_host: usize,
+    retries: usize,
+    backoff: Duration,
+}
+
+impl Default for NetworkOptions {
+    fn default() -> Self {
+        Self {
+            connections_per_host: 4,
+            retries: 3,
+            backoff: Duration::from_millis(500),
+        }
+    }
+}
+
+impl NetworkOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Most connections open to one host at a time. Zero means no limit.
+    pub fn connections_per_host(mut self, connections: usize) -> Self {
+        self.connections_per_host = connections;
+        self
+    }
+
+    /// Retries after the first attempt fails with `Error::Network`
+    pub fn retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Wait before the first retry, doubled for each one after it
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Run `attempt` against the host in `url` once a connection slot is free,
+    /// retrying network errors and counting each retry in `retries`.
+    pub(crate) fn connect<T, F>(
+        &self,
+        url: &str,
+        retries: &Cell<usize>,
+        mut attempt: F,
+    ) -> Result<T, Error>
+    where
+        F: FnMut() -> Result<T, Error>,
+    {
+        let host = credentials::host_from_url(url).unwrap_or_default();
+        let mut backoff = self.backoff;
+        let mut retried = 0;
+
+        loop {
+            let result = {
+                let _slot = ConnectionSlot::acquire(&host, self.connections_per_host);
+                attempt()
+            };
+
+            match result {
+                Err(Error::Network(_)) if retried < self.retries => {
+                    retried += 1;
+                    retries.set(retries.get() + 1);
+
+                    thread::sleep(backoff);
+                    backoff *= 2;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Map a failed connection to `Error::Network` when it timed out or was reset,
+/// the only failures worth retrying, and other connection failures to
+/// `Error::Remote`. libgit2 reports all of them as generic errors, so they
+/// can only be told apart by their message.
+pub(crate) fn remote_error(error: git2::Error) -> Error {
+    let is_network_class = matches!(
+        error.class(),
+        git2::ErrorClass::Net | git2::ErrorClass::Os | git2::ErrorClass::Ssh
+    );
+    let message = error.message().to_lowercase();
+    let is_transient = message.contains("timed out")
+        || message.contains("timeout")
+        || message.contains("connection reset")
+        || message.contains("reset by peer");
+
+    match (error.code(), is_network_class, is_transient) {
+        (git2::ErrorCode::GenericError, true, true) => Error::Network(error.message().into()),
+        (git2::ErrorCode::GenericError, true, false) => Error::Remote(error.message().into()),
+        _ => error.into(),
+    }
+}
+
+/// Held for the length of one connection. Local remotes have no host and are
+/// never limited.
+struct ConnectionSlot {
+    host: Option<String>,
+}
+
+impl ConnectionSlot {
+    fn acquire(host: &str, limit: usize) -> Self {
+        if host.is_empty() || limit == 0 {
+            return Self { host: None };
+        }
+
+        let mut open = OPEN_CONNECTIONS
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        while open.get(host).cloned().unwrap_or_default() >= limit {
+            open = CONNECTION_CLOSED
+                .wait(open)
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+
+        *open.entry(host.into()).or_insert(0) += 1;
+
+        Self {
+            host: Some(host.into()),
+        }
+    }
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        let host = match self.host {
+            Some(ref host) => host,
+            None => return,
+        };
+
+        let mut open = OPEN_CONNECTIONS
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        if let Some(count) = open.get_mut(host) {
+            *count -= 1;
+
+            if *count == 0 {
+                open.remove(host);
+            }
+        }
+
+        CONNECTION_CLOSED.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn generic(class: git2::ErrorClass, message: &str) -> git2::Error {
+        git2::Error::new(git2::ErrorCode::GenericError, class, message)
+    }
+
+    #[test]
+    fn timeouts_and_resets_are_network_errors() {
+        let errors = vec![
+            generic(git2::ErrorClass::Net, "operation timed out"),
+            generic(git2::ErrorClass::Os, "Connection reset by peer"),
+            generic(git2::ErrorClass::Ssh, "Timeout waiting for status"),
+        ];
+
+        for error in errors {
+            match remote_error(error) {
+                Error::Network(_) => {}
+                other => panic!("Expected a network error, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn other_failures_are_remote_errors() {
+        let errors = vec![
+            generic(
+                git2::ErrorClass::Os,
+                "failed to connect: Connection refused",
+            ),
+            generic(git2::ErrorClass::Net, "unexpected http status code: 404"),
+            generic(git2::ErrorClass::Ssh, "Failed to authenticate SSH session"),
+        ];
+
+        for error in errors {
+            match remote_error(error) {
+                Error::Remote(_) => {}
+                other => panic!("Expected a remote error, got {:?}", other),
+            }
+        }
+    }
+}
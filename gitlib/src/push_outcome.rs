@@ -0,0 +1,13 @@
+/// What `GitRepo::push_head` did with the checked out branch.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PushOutcome {
+    /// Pushed to the named upstream, like `origin/main`
+    Pushed {
+        upstream: String,
+    },
+    UpToDate {
+        upstream: String,
+    },
+    Detached,
+    NoUpstream,
+}
@@ -18,4 +18,15 @@ impl Reference {
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    /// Name without the `refs/heads/`, `refs/remotes/` or `refs/tags/` prefix
+    pub fn short_name(&self) -> &str {
+        const PREFIXES: [&str; 3] = ["refs/heads/", "refs/remotes/", "refs/tags/"];
+
+        PREFIXES
+            .iter()
+            .find(|x| self.name.starts_with(*x))
+            .map(|x| &self.name[x.len()..])
+            .unwrap_or(&self.name)
+    }
 }
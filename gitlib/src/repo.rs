@@ -1,15 +1,22 @@
-use crate::{credentials::Credentials, Error, Reference, Statuses};
+use crate::{
+    credentials::{self, CredentialHandler},
+    Branch, BranchInfo, CommitOutcome, Conflict, Diff, DiffRange, Error, FetchOptions, FetchResult,
+    FileVersion, HeadState, MergeOutcome, NetworkOptions, Operation, PushOutcome, Reference, Stash,
+    StatusOptions, Statuses, UpdatedRef,
+};
 use git2;
-use std::path::{Path, PathBuf};
-use url::Url;
+use std::{
+    cell::Cell,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 pub struct GitRepo {
     path: PathBuf,
     repo: git2::Repository,
+    retries: Cell<usize>,
 }
 
-unsafe impl Send for GitRepo {}
-
 impl GitRepo {
     pub fn open<P>(path: P) -> Result<Self, Error>
     where
@@ -22,24 +29,23 @@ impl GitRepo {
         let repo = Self {
             path: owned_path,
             repo: git_repo,
+            retries: Cell::new(0),
         };
 
         Ok(repo)
     }
 
+    /// Network retries made by every fetch and push on this repo so far
+    pub fn network_retries(&self) -> usize {
+        self.retries.get()
+    }
+
     pub fn path(&self) -> &Path {
         &self.path
     }
 
-    pub fn statuses(&self) -> Result<Statuses<'_>, Error> {
-        let mut opts = git2::StatusOptions::new();
-
-        opts.include_ignored(false)
-            .include_untracked(true)
-            .recurse_untracked_dirs(true)
-            .include_unreadable_as_untracked(true)
-            .disable_pathspec_match(true)
-            .exclude_submodules(true);
+    pub fn statuses(&self, options: &StatusOptions) -> Result<Statuses, Error> {
+        let mut opts = options.to_git2();
 
         self.repo
             .statuses(Some(&mut opts))
@@ -47,6 +53,119 @@ impl GitRepo {
             .map_err(|x| x.into())
     }
 
+    /// Files changed between the two sides of `range`, with line counts and,
+    /// when `patch` is set, the patch text of each file
+    pub fn diff(&self, range: &DiffRange, patch: bool) -> Result<Diff, Error> {
+        let diff = match *range {
+            DiffRange::Worktree => self.repo.diff_index_to_workdir(None, None)?,
+            DiffRange::Index => {
+                // An unborn branch has no tree, so everything staged is new
+                let head = match self.repo.head() {
+                    Ok(head) => Some(head.peel_to_tree()?),
+                    Err(ref e) if e.code() == git2::ErrorCode::UnbornBranch => None,
+                    Err(e) => return Err(e.into()),
+                };
+
+                self.repo.diff_tree_to_index(head.as_ref(), None, None)?
+            }
+            DiffRange::Revs(ref from, ref to) => {
+                let from = self.repo.revparse_single(from)?.peel_to_tree()?;
+                let to = self.repo.revparse_single(to)?.peel_to_tree()?;
+
+                self.repo.diff_tree_to_tree(Some(&from), Some(&to), None)?
+            }
+        };
+
+        Diff::new(diff, patch)
+    }
+
+    /// The index sides of a conflicted file, or `None` when `path` isn't conflicted
+    pub fn conflict(&self, path: &Path) -> Result<Option<Conflict>, Error> {
+        let index = self.repo.index()?;
+        let path_bytes = path.to_str().ok_or(Error::InvalidUtf8)?.as_bytes();
+
+        for conflict in index.conflicts()? {
+            let conflict = conflict?;
+            let is_match = [&conflict.ancestor, &conflict.our, &conflict.their]
+                .iter()
+                .any(|x| x.as_ref().is_some_and(|x| x.path == path_bytes));
+
+            if is_match {
+                let version =
+                    |x: &Option<git2::IndexEntry>| x.as_ref().map(FileVersion::from_index_entry);
+
+                return Ok(Some(Conflict {
+                    ancestor: version(&conflict.ancestor),
+                    ours: version(&conflict.our),
+                    theirs: version(&conflict.their),
+                }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Checked out branch, how it compares to its upstream, and any merge or
+    /// rebase in progress
+    pub fn head_state(&self) -> Result<HeadState, Error> {
+        let mut state = HeadState {
+            branch: None,
+            detached_at: None,
+            upstream: None,
+            upstream_gone: false,
+            ahead: 0,
+            behind: 0,
+            unpushed: 0,
+            operation: Operation::from_state(self.repo.state()),
+        };
+
+        let head = match self.repo.head() {
+            Ok(head) => head,
+            // Nothing has been committed to the branch yet
+            Err(ref e) if e.code() == git2::ErrorCode::UnbornBranch => {
+                let head = self.repo.find_reference("HEAD")?;
+                state.branch = head
+                    .symbolic_target()
+                    .map(|x| x.trim_start_matches("refs/heads/").into());
+
+                return Ok(state);
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let head_id = head.peel_to_commit()?.id();
+        state.unpushed = self.unpushed_commits(head_id)?;
+
+        if self.repo.head_detached()? {
+            state.detached_at = Some(head_id.to_string()[..7].into());
+            return Ok(state);
+        }
+
+        let refname = head.name().ok_or(Error::InvalidUtf8)?.to_string();
+        let branch = git2::Branch::wrap(head);
+        state.branch = branch.name()?.map(|x| x.into());
+
+        match branch.upstream() {
+            Ok(upstream) => {
+                let upstream_id = upstream.get().peel_to_commit()?.id();
+                let (ahead, behind) = self.repo.graph_ahead_behind(head_id, upstream_id)?;
+
+                state.upstream = upstream.name()?.map(|x| x.into());
+                state.ahead = ahead;
+                state.behind = behind;
+            }
+            Err(_) => state.upstream_gone = self.repo.branch_upstream_name(&refname).is_ok(),
+        }
+
+        Ok(state)
+    }
+
+    pub fn head(&self) -> Result<Reference, Error> {
+        let head = self.repo.head()?;
+
+        Reference::from_ref(&head)
+    }
+
     pub fn reset(&self) -> Result<Reference, Error> {
         let head = self.repo.head()?;
         let obj = head.peel(git2::ObjectType::Any)?;
@@ -67,6 +186,67 @@ impl GitRepo {
         }
     }
 
+    /// Local branches, followed by remote-tracking branches when `remotes` is set.
+    pub fn branches(&self, remotes: bool) -> Result<Vec<Branch>, Error> {
+        let branch_type = if remotes {
+            None
+        } else {
+            Some(git2::BranchType::Local)
+        };
+
+        let mut branches = Vec::new();
+
+        for branch in self.repo.branches(branch_type)? {
+            let (branch, branch_type) = branch?;
+
+            // Skip symbolic refs like `origin/HEAD`
+            if branch.get().kind() == Some(git2::ReferenceType::Symbolic) {
+                continue;
+            }
+
+            branches.push(Branch::from_branch(&branch, branch_type)?);
+        }
+
+        branches.sort_by_key(|x| x.is_remote());
+
+        Ok(branches)
+    }
+
+    /// Short name of the default branch, without any remote prefix.
+    pub fn default_branch_name(&self) -> Result<String, Error> {
+        let default = self.default_branch()?;
+        let name = default.name().ok_or(Error::InvalidUtf8)?;
+
+        Ok(short_branch_name(name).into())
+    }
+
+    /// Create a local branch pointing at the commit `base` resolves to.
+    pub fn create_branch(&self, branch_name: &str, base: &str) -> Result<(), Error> {
+        let commit = self.repo.revparse_single(base)?.peel_to_commit()?;
+
+        self.repo.branch(branch_name, &commit, false)?;
+
+        Ok(())
+    }
+
+    /// Track `upstream`, a branch name like `origin/main`.
+    pub fn set_upstream(&self, branch_name: &str, upstream: &str) -> Result<(), Error> {
+        self.repo
+            .find_branch(branch_name, git2::BranchType::Local)?
+            .set_upstream(Some(upstream))?;
+
+        Ok(())
+    }
+
+    /// Rename a local branch. Fails with `Error::Exists` if `new_name` is taken.
+    pub fn rename_branch(&self, branch_name: &str, new_name: &str) -> Result<(), Error> {
+        self.repo
+            .find_branch(branch_name, git2::BranchType::Local)?
+            .rename(new_name, false)?;
+
+        Ok(())
+    }
+
     pub fn delete_local_branch(&self, branch_name: &str) -> Result<(), Error> {
         self.repo
             .find_branch(branch_name, git2::BranchType::Local)?
@@ -75,34 +255,323 @@ impl GitRepo {
         Ok(())
     }
 
-    pub fn fetch(&self) -> Result<(), Error> {
-        let refspecs = self.repo.find_remote("origin")?.fetch_refspecs()?;
+    /// A branch counts as merged when its tip is reachable from its upstream
+    /// or from the repo's default branch. The default branch is never merged
+    /// into itself.
+    pub fn is_branch_merged(&self, branch_name: &str) -> Result<bool, Error> {
+        let branch = self
+            .repo
+            .find_branch(branch_name, git2::BranchType::Local)?;
+        let tip = branch.get().peel_to_commit()?.id();
+
+        if let Ok(upstream) = branch.upstream() {
+            let upstream_tip = upstream.get().peel_to_commit()?.id();
+
+            if self.is_ancestor(tip, upstream_tip)? {
+                return Ok(true);
+            }
+        }
+
+        self.is_merged_into_default(&branch)
+    }
+
+    pub fn branch_info(&self, branch_name: &str) -> Result<BranchInfo, Error> {
+        let branch = self
+            .repo
+            .find_branch(branch_name, git2::BranchType::Local)?;
+        let commit = branch.get().peel_to_commit()?;
+
+        let refname = format!("refs/heads/{}", branch_name);
+        let (upstream, ahead, behind) = match branch.upstream() {
+            Ok(upstream) => {
+                let upstream_tip = upstream.get().peel_to_commit()?.id();
+                let (ahead, behind) = self.repo.graph_ahead_behind(commit.id(), upstream_tip)?;
+                let name = upstream.name()?.map(|x| x.into());
+
+                (name, ahead, behind)
+            }
+            Err(_) => (None, 0, 0),
+        };
+
+        // An upstream that is configured but can't be found was deleted on the remote.
+        let upstream_gone = upstream.is_none() && self.repo.branch_upstream_name(&refname).is_ok();
+
+        let author = commit.author().name().unwrap_or_default().to_string();
+        let seconds = commit.time().seconds().max(0) as u64;
+
+        Ok(BranchInfo {
+            name: branch_name.into(),
+            is_head: branch.is_head(),
+            upstream,
+            upstream_gone,
+            ahead,
+            behind,
+            author,
+            time: UNIX_EPOCH + Duration::from_secs(seconds),
+            is_merged: self.is_merged_into_default(&branch)?,
+        })
+    }
+
+    pub fn delete_remote_branch(&self, remote_name: &str, branch_name: &str) -> Result<(), Error> {
+        let refspec = format!(":refs/heads/{}", branch_name);
+
+        self.push(remote_name, &[refspec.as_str()], &NetworkOptions::new())?;
+
+        // The remote-tracking branch is stale now, so drop it too.
+        self.delete_tracking_branch(&format!("{}/{}", remote_name, branch_name))
+    }
+
+    /// Push the local `new_name` branch to the remote, delete `branch_name` there
+    /// and track the pushed branch. Fails with `Error::NotFound` if the remote
+    /// never had `branch_name`.
+    pub fn rename_remote_branch(
+        &self,
+        remote_name: &str,
+        branch_name: &str,
+        new_name: &str,
+    ) -> Result<(), Error> {
+        let tracking_name = format!("{}/{}", remote_name, branch_name);
+        self.repo
+            .find_branch(&tracking_name, git2::BranchType::Remote)?;
+
+        let push_refspec = format!("refs/heads/{0}:refs/heads/{0}", new_name);
+        let delete_refspec = format!(":refs/heads/{}", branch_name);
+
+        self.push(
+            remote_name,
+            &[push_refspec.as_str(), delete_refspec.as_str()],
+            &NetworkOptions::new(),
+        )?;
+
+        self.delete_tracking_branch(&tracking_name)?;
+
+        self.set_upstream(new_name, &format!("{}/{}", remote_name, new_name))
+    }
+
+    pub fn remote_names(&self) -> Result<Vec<String>, Error> {
+        let remotes = self.repo.remotes()?;
+
+        Ok(remotes.iter().filter_map(|x| x.map(|x| x.into())).collect())
+    }
+
+    pub fn fetch(&self, remote_name: &str, options: &FetchOptions) -> Result<FetchResult, Error> {
+        let refspecs = self.repo.find_remote(remote_name)?.fetch_refspecs()?;
         let refspec_collection = refspecs.iter().filter_map(|x| x).collect::<Vec<_>>();
+        let mut remote = self.connectable_remote(remote_name)?;
+
+        let url = remote.url().unwrap_or_default().to_string();
+
+        let updated_refs = options.network_options().connect(&url, &self.retries, || {
+            let mut updated_refs = Vec::new();
+            let mut credentials = CredentialHandler::new(self.repo.config().ok());
+
+            let fetched = {
+                let mut remote_callbacks = git2::RemoteCallbacks::new();
+                remote_callbacks.credentials(|url, username, allowed| {
+                    credentials.credentials(url, username, allowed)
+                });
+                remote_callbacks.update_tips(|name, old_id, new_id| {
+                    updated_refs.push(UpdatedRef::new(name, old_id, new_id));
+                    true
+                });
+
+                let mut fetch_options = git2::FetchOptions::new();
+                fetch_options
+                    .remote_callbacks(remote_callbacks)
+                    .prune(options.prune_option())
+                    .download_tags(options.autotag_option());
+
+                // TODO: Instead of refspec_collection, maybe the following:
+                // &["refs/heads/*:refs/heads/*"]
+                // Example here: https://github.com/rust-lang/crates.io/blob/master/src/git.rs#L114-L209
+
+                remote.fetch(&refspec_collection, Some(&mut fetch_options), None)
+            };
+
+            fetched.map_err(|e| credentials.error_for(e))?;
+
+            Ok(updated_refs)
+        })?;
+
+        Ok(FetchResult::new(remote_name, updated_refs, &remote.stats()))
+    }
+
+    /// Remote tracked by the checked out branch
+    pub fn head_upstream_remote(&self) -> Result<String, Error> {
+        let head = self.repo.head()?;
+        let name = head.name().ok_or(Error::InvalidUtf8)?;
+        let remote = self.repo.branch_upstream_remote(name)?;
+
+        remote.as_str().map(|x| x.into()).ok_or(Error::InvalidUtf8)
+    }
+
+    /// Bring the checked out branch up to date with its upstream, using only
+    /// what has already been fetched. Fast-forwards when possible, and rebases
+    /// local commits onto the upstream when `rebase` is set.
+    pub fn merge_upstream(&self, rebase: bool) -> Result<MergeOutcome, Error> {
+        self.update_from_upstream(rebase, false)
+    }
+
+    /// What `merge_upstream` would do, without touching the branch or work tree.
+    /// A preview never reports `MergeOutcome::Conflict`.
+    pub fn preview_merge_upstream(&self, rebase: bool) -> Result<MergeOutcome, Error> {
+        self.update_from_upstream(rebase, true)
+    }
+
+    /// When the last fetch of any remote finished
+    pub fn last_fetched(&self) -> Option<SystemTime> {
+        self.repo
+            .path()
+            .join("FETCH_HEAD")
+            .metadata()
+            .and_then(|x| x.modified())
+            .ok()
+    }
+
+    fn update_from_upstream(&self, rebase: bool, dry_run: bool) -> Result<MergeOutcome, Error> {
+        if self.repo.head_detached()? {
+            return Ok(MergeOutcome::Detached);
+        }
+
+        if self.repo.state() != git2::RepositoryState::Clean || self.has_tracked_changes()? {
+            return Ok(MergeOutcome::Dirty);
+        }
+
+        let head = self.repo.head()?;
+        let branch = git2::Branch::wrap(head);
+
+        let upstream = match branch.upstream() {
+            Ok(u) => u,
+            Err(ref e) if e.code() == git2::ErrorCode::NotFound => {
+                return Ok(MergeOutcome::NoUpstream)
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let upstream_commit = self.repo.reference_to_annotated_commit(upstream.get())?;
+        let (analysis, _) = self.repo.merge_analysis(&[&upstream_commit])?;
+
+        if analysis.is_up_to_date() {
+            return Ok(MergeOutcome::UpToDate);
+        }
+
+        let local = branch.get().peel_to_commit()?.id();
+
+        if analysis.is_fast_forward() && dry_run {
+            return Ok(MergeOutcome::FastForwarded {
+                from: local.to_string(),
+                to: upstream_commit.id().to_string(),
+            });
+        }
+
+        if analysis.is_fast_forward() {
+            return self.fast_forward(branch.into_reference(), upstream_commit.id());
+        }
+
+        let (ahead, behind) = self.repo.graph_ahead_behind(local, upstream_commit.id())?;
+
+        if !rebase {
+            return Ok(MergeOutcome::Diverged { ahead, behind });
+        }
+
+        if dry_run {
+            return Ok(MergeOutcome::Rebased { commits: ahead });
+        }
+
+        let branch_commit = self.repo.reference_to_annotated_commit(branch.get())?;
+
+        self.rebase(&branch_commit, &upstream_commit)
+    }
+
+    /// Push the checked out branch to its upstream. Without an upstream, push
+    /// to the same name on origin and track it when `set_upstream` is set.
+    pub fn push_head(
+        &self,
+        set_upstream: bool,
+        network: &NetworkOptions,
+    ) -> Result<PushOutcome, Error> {
+        self.push_head_to_upstream(set_upstream, Some(network))
+    }
 
-        let mut fetch_options = {
-            let mut remote_callbacks = git2::RemoteCallbacks::new();
-            remote_callbacks.credentials(Self::credentials_callback);
+    /// What `push_head` would do, judged from the remote-tracking branch.
+    /// A push the remote would refuse fails with `Error::NotFastForward`.
+    pub fn preview_push_head(&self, set_upstream: bool) -> Result<PushOutcome, Error> {
+        self.push_head_to_upstream(set_upstream, None)
+    }
+
+    /// Pushes over `network`, or only previews the push when it's `None`.
+    fn push_head_to_upstream(
+        &self,
+        set_upstream: bool,
+        network: Option<&NetworkOptions>,
+    ) -> Result<PushOutcome, Error> {
+        if self.repo.head_detached()? {
+            return Ok(PushOutcome::Detached);
+        }
+
+        let head = self.repo.head()?;
+        let refname = head.name().ok_or(Error::InvalidUtf8)?.to_string();
+        let branch = git2::Branch::wrap(head);
+        let branch_name = branch.name()?.ok_or(Error::InvalidUtf8)?.to_string();
+
+        let (remote_name, remote_ref) = match self.repo.branch_upstream_remote(&refname) {
+            Ok(remote) => {
+                let remote = remote.as_str().ok_or(Error::InvalidUtf8)?.to_string();
+                let merge = self
+                    .repo
+                    .config()?
+                    .get_string(&format!("branch.{}.merge", branch_name))?;
 
-            let mut o = git2::FetchOptions::new();
-            o.remote_callbacks(remote_callbacks);
-            o
+                (remote, merge)
+            }
+            Err(ref e) if e.code() == git2::ErrorCode::NotFound && set_upstream => {
+                ("origin".to_string(), refname.clone())
+            }
+            Err(ref e) if e.code() == git2::ErrorCode::NotFound => {
+                return Ok(PushOutcome::NoUpstream)
+            }
+            Err(e) => return Err(e.into()),
         };
 
-        // TODO: Instead of refspec_collection, maybe the following:
-        // &["refs/heads/*:refs/heads/*"]
-        // Example here: https://github.com/rust-lang/crates.io/blob/master/src/git.rs#L114-L209
+        let upstream = format!(
+            "{}/{}",
+            remote_name,
+            remote_ref.trim_start_matches("refs/heads/")
+        );
 
-        if let Err(e) = self.repo.find_remote("origin")?.fetch(
-            &refspec_collection,
-            Some(&mut fetch_options),
-            None,
-        ) {
-            let asd = format!("{:?}", e);
-            dbg!(&self.path);
-            return Err(Error::GenericError);
+        let local_id = branch.get().peel_to_commit()?.id();
+        let remote_id = self
+            .repo
+            .find_branch(&upstream, git2::BranchType::Remote)
+            .ok()
+            .and_then(|x| x.get().target());
+
+        if remote_id == Some(local_id) {
+            return Ok(PushOutcome::UpToDate { upstream });
         }
 
-        Ok(())
+        let network = match network {
+            Some(network) => network,
+            None => {
+                if let Some(remote_id) = remote_id {
+                    if !self.repo.graph_descendant_of(local_id, remote_id)? {
+                        return Err(Error::NotFastForward);
+                    }
+                }
+
+                return Ok(PushOutcome::Pushed { upstream });
+            }
+        };
+
+        let refspec = format!("{}:{}", refname, remote_ref);
+
+        self.push(&remote_name, &[refspec.as_str()], network)?;
+
+        if set_upstream {
+            self.set_upstream(&branch_name, &upstream)?;
+        }
+
+        Ok(PushOutcome::Pushed { upstream })
     }
 
     pub fn has_local_branch(&self, branch_name: &str) -> Result<(), Error> {
@@ -112,6 +581,488 @@ impl GitRepo {
         Ok(())
     }
 
+    pub fn has_remote_branch(&self, branch_name: &str) -> Result<(), Error> {
+        self.repo
+            .find_branch(branch_name, git2::BranchType::Remote)?;
+
+        Ok(())
+    }
+
+    fn delete_tracking_branch(&self, tracking_name: &str) -> Result<(), Error> {
+        if let Ok(mut tracking) = self
+            .repo
+            .find_branch(tracking_name, git2::BranchType::Remote)
+        {
+            tracking.delete()?;
+        }
+
+        Ok(())
+    }
+
+    /// Stash entries, newest first
+    pub fn stashes(&self) -> Result<Vec<Stash>, Error> {
+        let reflog = self.repo.reflog("refs/stash")?;
+
+        let stashes = reflog
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                Stash::new(
+                    index,
+                    entry.message().unwrap_or_default(),
+                    entry.committer().when(),
+                )
+            })
+            .collect();
+
+        Ok(stashes)
+    }
+
+    /// Stage every change to tracked files, and new files too when `all` is
+    /// set, then commit them as the configured user. `amend` folds them into
+    /// HEAD instead, unless HEAD is already on a remote. Returns `None` when
+    /// nothing changed.
+    pub fn commit(
+        &self,
+        message: &str,
+        all: bool,
+        amend: bool,
+    ) -> Result<Option<CommitOutcome>, Error> {
+        let head = self.commit_head()?;
+        let mut index = self.repo.index()?;
+
+        if all {
+            index.add_all(["*"], git2::IndexAddOption::DEFAULT, None)?;
+        }
+
+        index.update_all(["*"], None)?;
+
+        let tree = self.repo.find_tree(index.write_tree()?)?;
+        let head_tree = head.as_ref().map(|x| x.tree()).transpose()?;
+        let files = self
+            .repo
+            .diff_tree_to_tree(head_tree.as_ref(), Some(&tree), None)?
+            .deltas()
+            .len();
+
+        let refusal = self.amend_refusal(head.as_ref(), amend)?;
+
+        // Without an identity nothing can be committed, so nothing is staged
+        let signature = self.signature();
+
+        if files == 0 || refusal.is_some() || signature.is_err() {
+            // Leave the index as it is on disk
+            index.read(true)?;
+        }
+
+        if files == 0 {
+            return Ok(None);
+        }
+
+        if let Some(refusal) = refusal {
+            return Err(refusal);
+        }
+
+        let signature = signature?;
+        index.write()?;
+
+        let id = match head {
+            Some(ref commit) if amend => commit.amend(
+                Some("HEAD"),
+                None,
+                Some(&signature),
+                None,
+                Some(message),
+                Some(&tree),
+            )?,
+            _ => {
+                let parents = head.iter().collect::<Vec<_>>();
+
+                self.repo.commit(
+                    Some("HEAD"),
+                    &signature,
+                    &signature,
+                    message,
+                    &tree,
+                    &parents,
+                )?
+            }
+        };
+
+        Ok(Some(CommitOutcome {
+            id: Some(id.to_string()),
+            amended: amended_id(head.as_ref(), amend),
+            files,
+        }))
+    }
+
+    /// What `commit` would do, without staging or committing anything. Files
+    /// are counted from the status, so nothing is written to the repo.
+    pub fn preview_commit(&self, all: bool, amend: bool) -> Result<Option<CommitOutcome>, Error> {
+        let head = self.commit_head()?;
+
+        let mut options = git2::StatusOptions::new();
+        options
+            .include_untracked(all)
+            .recurse_untracked_dirs(all)
+            .include_ignored(false);
+
+        let files = self
+            .repo
+            .statuses(Some(&mut options))?
+            .iter()
+            .filter(|x| x.status() != git2::Status::CURRENT)
+            .count();
+
+        if files == 0 {
+            return Ok(None);
+        }
+
+        if let Some(refusal) = self.amend_refusal(head.as_ref(), amend)? {
+            return Err(refusal);
+        }
+
+        self.signature()?;
+
+        Ok(Some(CommitOutcome {
+            id: None,
+            amended: amended_id(head.as_ref(), amend),
+            files,
+        }))
+    }
+
+    /// HEAD's commit, or `None` on an unborn branch, if the repo can take a
+    /// commit at all
+    fn commit_head(&self) -> Result<Option<git2::Commit<'_>>, Error> {
+        if self.repo.is_bare() {
+            return Err(Error::BareRepo);
+        }
+
+        if self.repo.state() != git2::RepositoryState::Clean {
+            return Err(Error::Unmerged);
+        }
+
+        match self.repo.head() {
+            Ok(head) => Ok(Some(head.peel_to_commit()?)),
+            Err(ref e) if e.code() == git2::ErrorCode::UnbornBranch => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Why `head` can't be amended, if `amend` is set and it can't
+    fn amend_refusal(
+        &self,
+        head: Option<&git2::Commit<'_>>,
+        amend: bool,
+    ) -> Result<Option<Error>, Error> {
+        let refusal = match head {
+            _ if !amend => None,
+            None => Some(Error::UnbornBranch),
+            Some(commit) => {
+                let has_remotes = !self.repo.remotes()?.is_empty();
+
+                if has_remotes && self.unpushed_commits(commit.id())? == 0 {
+                    Some(Error::AlreadyPushed)
+                } else {
+                    None
+                }
+            }
+        };
+
+        Ok(refusal)
+    }
+
+    /// The configured user, which must have both a name and an email
+    fn signature(&self) -> Result<git2::Signature<'static>, Error> {
+        let config = self.repo.config()?;
+
+        for key in &["user.name", "user.email"] {
+            match config.get_string(key) {
+                Ok(value) if !value.trim().is_empty() => {}
+                Ok(_) => return Err(Error::NoIdentity),
+                Err(ref e) if e.code() == git2::ErrorCode::NotFound => {
+                    return Err(Error::NoIdentity)
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Ok(self.repo.signature()?)
+    }
+
+    /// Stash changes to tracked files, and untracked files too when
+    /// `include_untracked` is set. Returns `None` when there was nothing to stash.
+    pub fn stash_push(
+        &mut self,
+        message: Option<&str>,
+        include_untracked: bool,
+    ) -> Result<Option<Stash>, Error> {
+        let signature = self.repo.signature()?;
+
+        // Like git, describe the stash by the commit it was made on when no
+        // message is given.
+        let message = match message {
+            Some(message) => message.to_string(),
+            None => {
+                let head = self.repo.head()?.peel_to_commit()?;
+                let id = head.id().to_string();
+
+                format!("{} {}", &id[..7], head.summary().unwrap_or_default())
+            }
+        };
+
+        let flags = if include_untracked {
+            git2::StashFlags::INCLUDE_UNTRACKED
+        } else {
+            git2::StashFlags::DEFAULT
+        };
+
+        match self.repo.stash_save(&signature, &message, Some(flags)) {
+            Ok(_) => Ok(self.stashes()?.into_iter().next()),
+            Err(ref e) if e.code() == git2::ErrorCode::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Apply `stash@{index}` and drop it. Nothing changes if it would
+    /// overwrite local changes, which fails with `Error::Conflict`.
+    pub fn stash_pop(&mut self, index: usize) -> Result<(), Error> {
+        self.repo.stash_pop(index, None)?;
+
+        Ok(())
+    }
+
+    pub fn stash_drop(&mut self, index: usize) -> Result<(), Error> {
+        self.repo.stash_drop(index)?;
+
+        Ok(())
+    }
+
+    /// Commits reachable from `id` but from no remote-tracking branch
+    fn unpushed_commits(&self, id: git2::Oid) -> Result<usize, Error> {
+        if self.repo.remotes()?.is_empty() {
+            return Ok(0);
+        }
+
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.push(id)?;
+        revwalk.hide_glob("refs/remotes/*")?;
+
+        Ok(revwalk.count())
+    }
+
+    /// Stages and commits when given a message, otherwise only reports.
+    fn has_tracked_changes(&self) -> Result<bool, Error> {
+        let mut opts = git2::StatusOptions::new();
+
+        opts.include_ignored(false)
+            .include_untracked(false)
+            .exclude_submodules(true);
+
+        Ok(!self.repo.statuses(Some(&mut opts))?.is_empty())
+    }
+
+    fn fast_forward(
+        &self,
+        mut reference: git2::Reference<'_>,
+        target: git2::Oid,
+    ) -> Result<MergeOutcome, Error> {
+        let from = reference.peel_to_commit()?.id();
+        let commit = self.repo.find_commit(target)?;
+
+        // Update the working tree first so a failed checkout leaves the branch alone.
+        self.repo.checkout_tree(
+            commit.as_object(),
+            Some(git2::build::CheckoutBuilder::new().safe()),
+        )?;
+        reference.set_target(target, "git-plz: fast-forward")?;
+
+        Ok(MergeOutcome::FastForwarded {
+            from: from.to_string(),
+            to: target.to_string(),
+        })
+    }
+
+    fn rebase(
+        &self,
+        branch: &git2::AnnotatedCommit<'_>,
+        upstream: &git2::AnnotatedCommit<'_>,
+    ) -> Result<MergeOutcome, Error> {
+        let signature = self.repo.signature()?;
+        let mut rebase = self.repo.rebase(Some(branch), Some(upstream), None, None)?;
+        let mut commits = 0;
+
+        while let Some(operation) = rebase.next() {
+            operation?;
+
+            if self.repo.index()?.has_conflicts() {
+                rebase.abort()?;
+                return Ok(MergeOutcome::Conflict);
+            }
+
+            match rebase.commit(None, &signature, None) {
+                Ok(_) => commits += 1,
+                // The upstream already has this change
+                Err(ref e) if e.code() == git2::ErrorCode::Applied => {}
+                Err(e) => {
+                    rebase.abort()?;
+                    return Err(e.into());
+                }
+            }
+        }
+
+        rebase.finish(Some(&signature))?;
+
+        Ok(MergeOutcome::Rebased { commits })
+    }
+
+    fn default_branch(&self) -> Result<git2::Reference<'_>, Error> {
+        if let Ok(head) = self.repo.find_reference("refs/remotes/origin/HEAD") {
+            if let Ok(resolved) = head.resolve() {
+                return Ok(resolved);
+            }
+        }
+
+        const CANDIDATES: [&str; 4] = [
+            "refs/heads/main",
+            "refs/heads/master",
+            "refs/remotes/origin/main",
+            "refs/remotes/origin/master",
+        ];
+
+        CANDIDATES
+            .iter()
+            .find_map(|name| self.repo.find_reference(name).ok())
+            .ok_or(Error::NotFound)
+    }
+
+    fn is_merged_into_default(&self, branch: &git2::Branch<'_>) -> Result<bool, Error> {
+        let default = match self.default_branch() {
+            Ok(d) => d,
+            Err(Error::NotFound) => return Ok(false),
+            Err(e) => return Err(e),
+        };
+
+        // The default is often `origin/main`, which stands for local `main` too
+        let default_name = default.name().map(short_branch_name);
+
+        if default_name.is_some() && default_name == branch.name()? {
+            return Ok(false);
+        }
+
+        let tip = branch.get().peel_to_commit()?.id();
+
+        self.is_ancestor(tip, default.peel_to_commit()?.id())
+    }
+
+    fn is_ancestor(&self, ancestor: git2::Oid, descendant: git2::Oid) -> Result<bool, Error> {
+        if ancestor == descendant {
+            return Ok(true);
+        }
+
+        match self.repo.merge_base(ancestor, descendant) {
+            Ok(base) => Ok(base == ancestor),
+            Err(ref e) if e.code() == git2::ErrorCode::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// `remote_name` ready to connect to. When its URL uses a host alias from
+    /// `~/.ssh/config`, this is an anonymous remote pointed at the alias's
+    /// `HostName` instead, which fetches with explicit refspecs but doesn't
+    /// update remote-tracking branches after a push.
+    fn connectable_remote(&self, remote_name: &str) -> Result<git2::Remote<'_>, Error> {
+        let remote = self.repo.find_remote(remote_name)?;
+
+        match remote.url().and_then(credentials::ssh_config_url) {
+            Some(url) => Ok(self.repo.remote_anonymous(&url)?),
+            None => Ok(remote),
+        }
+    }
+
+    /// Point the remote-tracking branches of `remote_name` at what `refspecs`
+    /// pushed, as libgit2 does for named remotes.
+    fn update_tracking_branches(&self, remote_name: &str, refspecs: &[&str]) -> Result<(), Error> {
+        for refspec in refspecs {
+            let (src, dst) = match refspec.trim_start_matches('+').split_once(':') {
+                Some(pair) => pair,
+                None => continue,
+            };
+
+            let tracking = match dst.strip_prefix("refs/heads/") {
+                Some(branch) => format!("refs/remotes/{}/{}", remote_name, branch),
+                None => continue,
+            };
+
+            if src.is_empty() {
+                if let Ok(mut reference) = self.repo.find_reference(&tracking) {
+                    reference.delete()?;
+                }
+            } else {
+                let id = self.repo.revparse_single(src)?.peel_to_commit()?.id();
+                self.repo.reference(&tracking, id, true, "update by push")?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Push `refspecs`, turning refs the remote refused into `Error::NotFastForward`
+    /// or `Error::Rejected`.
+    fn push(
+        &self,
+        remote_name: &str,
+        refspecs: &[&str],
+        network: &NetworkOptions,
+    ) -> Result<(), Error> {
+        let mut remote = self.connectable_remote(remote_name)?;
+        let url = remote.url().unwrap_or_default().to_string();
+
+        network.connect(&url, &self.retries, || {
+            let mut rejection = None;
+            let mut credentials = CredentialHandler::new(self.repo.config().ok());
+
+            let pushed = {
+                let mut remote_callbacks = git2::RemoteCallbacks::new();
+                remote_callbacks.credentials(|url, username, allowed| {
+                    credentials.credentials(url, username, allowed)
+                });
+                remote_callbacks.push_update_reference(|_, status| {
+                    if let Some(status) = status {
+                        let error =
+                            if status.contains("fast-forward") || status.contains("fetch first") {
+                                Error::NotFastForward
+                            } else {
+                                Error::Rejected
+                            };
+
+                        rejection = Some(error);
+                    }
+
+                    Ok(())
+                });
+
+                let mut push_options = git2::PushOptions::new();
+                push_options.remote_callbacks(remote_callbacks);
+
+                remote.push(refspecs, Some(&mut push_options))
+            };
+
+            pushed.map_err(|e| credentials.error_for(e))?;
+
+            match rejection {
+                Some(e) => Err(e),
+                None => Ok(()),
+            }
+        })?;
+
+        if remote.name().is_none() {
+            self.update_tracking_branches(remote_name, refspecs)?;
+        }
+
+        Ok(())
+    }
+
     fn get_branch_type(&self, branch_name: &str) -> Result<git2::BranchType, Error> {
         let components = branch_name.split('/').collect::<Vec<_>>();
 
@@ -152,54 +1103,24 @@ impl GitRepo {
 
         Ok(true)
     }
+}
 
-    fn credentials_callback(
-        user: &str,
-        user_from_url: Option<&str>,
-        cred: git2::CredentialType,
-    ) -> Result<git2::Cred, git2::Error> {
-        let credentials = Credentials::from(cred).collect::<Vec<_>>();
-
-        dbg!(&credentials);
-
-        let usr = String::from(user);
-        let usr_url = format!("{:?}", user_from_url);
-
-        // So far, this is usually meaning one of many SSH types. Need to handle better.
-        if credentials.len() > 1 {
-            let private_key = Path::new("~/.ssh/id_rsa");
-            let public_key = Path::new("~/.ssh/id_rsa.pub");
-
-            return git2::Cred::ssh_key(
-                user_from_url.expect("Could not unwrap `user_from_url`"),
-                Some(public_key),
-                private_key,
-                None,
-            );
-        }
-
-        let url = Url::parse(user).expect("Could not parse url from `user`");
-
-        if url.scheme() != "ssh" {
-            return Err(git2::Error::from_str("nooope"));
-        }
-
-        // At this point, user will be the hostname.
-        // Use it to get a username from the correct host out of
-        // ~/.ssh/config
-
-        /*fn username_for_host<'a>(host_name: &str) -> &'a str {
-            let hosts = ssh_config::parse("~/.ssh/config").expect("Could not parse ssh config");
-
-            hosts
-                .filter(|x| x.name == host_name)
-                .map(|x| x.properties.filter(|x| x.key == "User"))
-        }
-
-        let username = username_for_host(url.host_str().expect("URL has no host str"))
-            .expect("Unable to get username from ssh config");
+/// `main` for either `refs/heads/main` or `refs/remotes/origin/main`
+fn short_branch_name(name: &str) -> &str {
+    match name.strip_prefix("refs/heads/") {
+        Some(short) => short,
+        None => name
+            .trim_start_matches("refs/remotes/")
+            .split_once('/')
+            .map(|(_, short)| short)
+            .unwrap_or(name),
+    }
+}
 
-        git2::Cred::username(username.as_str())*/
-        git2::Cred::username("DrBucket")
+/// Short id of the commit being replaced, when amending
+fn amended_id(head: Option<&git2::Commit<'_>>, amend: bool) -> Option<String> {
+    match head {
+        Some(commit) if amend => Some(commit.id().to_string()[..7].into()),
+        _ => None,
     }
 }
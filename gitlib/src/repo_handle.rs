@@ -0,0 +1,57 @@
+use crate::{Error, GitRepo};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// A repo found on disk, cheap to send between threads. The repo itself is
+/// only opened by `open`, on whichever thread does the work.
+#[derive(Debug, Clone)]
+pub struct RepoHandle {
+    path: PathBuf,
+    is_bare: bool,
+}
+
+impl RepoHandle {
+    /// Fails with `NotFound` when `path` isn't the root of a repo. Only looks
+    /// for the files git needs, so discovery never opens the repo itself.
+    pub fn new<P>(path: P) -> Result<Self, Error>
+    where
+        P: Into<PathBuf>,
+    {
+        let path = path.into();
+        let dot_git = path.join(".git");
+
+        let is_bare = if is_git_dir(&dot_git) || is_gitdir_file(&dot_git) {
+            false
+        } else if is_git_dir(&path) {
+            true
+        } else {
+            return Err(Error::NotFound);
+        };
+
+        Ok(Self { path, is_bare })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn is_bare(&self) -> bool {
+        self.is_bare
+    }
+
+    pub fn open(&self) -> Result<GitRepo, Error> {
+        GitRepo::open(self.path.as_path())
+    }
+}
+
+/// The same check git makes before treating a directory as a repo
+fn is_git_dir(path: &Path) -> bool {
+    path.join("HEAD").is_file() && path.join("objects").is_dir() && path.join("refs").is_dir()
+}
+
+/// Linked worktrees and submodules have a `.git` file pointing at the repo
+fn is_gitdir_file(path: &Path) -> bool {
+    path.is_file() && fs::read_to_string(path).is_ok_and(|x| x.starts_with("gitdir:"))
+}
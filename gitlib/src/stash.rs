@@ -0,0 +1,53 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// One entry of `git stash list`
+#[derive(Debug, Clone)]
+pub struct Stash {
+    index: usize,
+    branch: Option<String>,
+    message: String,
+    time: SystemTime,
+}
+
+impl Stash {
+    /// Git writes stash messages as `WIP on <branch>: <id> <subject>` or,
+    /// when a message was given, `On <branch>: <message>`.
+    pub(crate) fn new(index: usize, message: &str, time: git2::Time) -> Self {
+        let message = message.trim();
+        let stripped = message
+            .strip_prefix("WIP on ")
+            .or_else(|| message.strip_prefix("On "));
+
+        let (branch, message) = match stripped.and_then(|x| x.split_once(": ")) {
+            Some((branch, message)) => (Some(branch.into()), message.into()),
+            None => (None, message.into()),
+        };
+
+        let seconds = time.seconds().max(0) as u64;
+
+        Self {
+            index,
+            branch: branch.filter(|x: &String| x != "(no branch)"),
+            message,
+            time: UNIX_EPOCH + Duration::from_secs(seconds),
+        }
+    }
+
+    /// Position in the stash list, as in `stash@{index}`
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Branch the stash was made on, or `None` for a detached HEAD
+    pub fn branch(&self) -> Option<&str> {
+        self.branch.as_deref()
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn time(&self) -> SystemTime {
+        self.time
+    }
+}
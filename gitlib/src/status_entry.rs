@@ -1,23 +1,143 @@
-use crate::StatusEntryIter;
+use crate::{Change, FileVersion, StatusEntryIter};
 use git2;
 use std::path::{Path, PathBuf};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct StatusEntry {
     path: PathBuf,
+    old_path: Option<PathBuf>,
     status: git2::Status,
+    pub(crate) head: Option<FileVersion>,
+    pub(crate) index: Option<FileVersion>,
+    pub(crate) worktree: Option<FileVersion>,
 }
 
 impl StatusEntry {
-    pub(crate) fn new(path: PathBuf, status: git2::Status) -> Self {
-        StatusEntry { path, status }
+    pub(crate) fn new(path: PathBuf, old_path: Option<PathBuf>, status: git2::Status) -> Self {
+        StatusEntry {
+            path,
+            old_path,
+            status,
+            head: None,
+            index: None,
+            worktree: None,
+        }
     }
 
     pub fn path(&self) -> &Path {
         &self.path
     }
 
+    /// Where a renamed file used to be. Only set when renames are detected.
+    pub fn old_path(&self) -> Option<&Path> {
+        self.old_path.as_deref()
+    }
+
+    /// The file in HEAD, when the index changed it
+    pub fn head(&self) -> Option<&FileVersion> {
+        self.head.as_ref()
+    }
+
+    /// The file in the index, when it differs from HEAD or the work tree
+    pub fn index(&self) -> Option<&FileVersion> {
+        self.index.as_ref()
+    }
+
+    /// The file in the work tree, when it differs from the index
+    pub fn worktree(&self) -> Option<&FileVersion> {
+        self.worktree.as_ref()
+    }
+
+    /// How the index differs from HEAD. `None` when the file is conflicted.
+    pub fn index_status(&self) -> Option<Change> {
+        let status = self.status;
+
+        // Edited renames are also flagged as modified, but git calls them renamed
+        if status.is_conflicted() {
+            None
+        } else if status.is_index_new() {
+            Some(Change::New)
+        } else if status.is_index_renamed() {
+            Some(Change::Renamed)
+        } else if status.is_index_modified() {
+            Some(Change::Modified)
+        } else if status.is_index_deleted() {
+            Some(Change::Deleted)
+        } else if status.is_index_typechange() {
+            Some(Change::Typechange)
+        } else {
+            None
+        }
+    }
+
+    /// How the work tree differs from the index, where `Change::New` means
+    /// untracked. `None` when the file is conflicted or ignored.
+    pub fn worktree_status(&self) -> Option<Change> {
+        let status = self.status;
+
+        if status.is_conflicted() {
+            None
+        } else if status.is_wt_new() {
+            Some(Change::New)
+        } else if status.is_wt_renamed() {
+            Some(Change::Renamed)
+        } else if status.is_wt_modified() {
+            Some(Change::Modified)
+        } else if status.is_wt_deleted() {
+            Some(Change::Deleted)
+        } else if status.is_wt_typechange() {
+            Some(Change::Typechange)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_conflicted(&self) -> bool {
+        self.status.is_conflicted()
+    }
+
+    pub fn is_ignored(&self) -> bool {
+        self.status.is_ignored()
+    }
+
+    /// `git status --porcelain` letter for `index_status`: one of `A`, `M`,
+    /// `D`, `R`, `T`, `U` for conflicts, `?` for untracked, `!` for ignored,
+    /// or `.`
+    pub fn index_code(&self) -> char {
+        match self.index_status() {
+            _ if self.is_conflicted() => 'U',
+            Some(Change::New) => 'A',
+            Some(change) => code(change),
+            None if self.worktree_status() == Some(Change::New) => '?',
+            None if self.is_ignored() => '!',
+            None => '.',
+        }
+    }
+
+    /// `git status --porcelain` letter for `worktree_status`: one of `M`,
+    /// `D`, `R`, `T`, `U` for conflicts, `?` for untracked, `!` for ignored,
+    /// or `.`
+    pub fn worktree_code(&self) -> char {
+        match self.worktree_status() {
+            _ if self.is_conflicted() => 'U',
+            Some(Change::New) => '?',
+            Some(change) => code(change),
+            None if self.is_ignored() => '!',
+            None => '.',
+        }
+    }
+
     pub fn iter(&self) -> StatusEntryIter<'_> {
         StatusEntryIter::new(&self.path, self.status)
     }
 }
+
+fn code(change: Change) -> char {
+    match change {
+        Change::New => 'A',
+        Change::Modified => 'M',
+        Change::Deleted => 'D',
+        Change::Renamed => 'R',
+        Change::Typechange => 'T',
+    }
+}
@@ -0,0 +1,48 @@
+/// Options for `GitRepo::statuses`. By default untracked files are listed,
+/// while ignored files, submodules and renames are left out.
+#[derive(Debug, Clone, Default)]
+pub struct StatusOptions {
+    ignored: bool,
+    submodules: bool,
+    renames: bool,
+}
+
+impl StatusOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// List ignored files as `Status::Ignored`
+    pub fn ignored(mut self, ignored: bool) -> Self {
+        self.ignored = ignored;
+        self
+    }
+
+    /// List submodules whose checked out commit or contents changed
+    pub fn submodules(mut self, submodules: bool) -> Self {
+        self.submodules = submodules;
+        self
+    }
+
+    /// Pair up deleted and added files that are renames, in both the index
+    /// and the work tree. Renamed entries carry their old path.
+    pub fn renames(mut self, renames: bool) -> Self {
+        self.renames = renames;
+        self
+    }
+
+    pub(crate) fn to_git2(&self) -> git2::StatusOptions {
+        let mut opts = git2::StatusOptions::new();
+
+        opts.include_ignored(self.ignored)
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .include_unreadable_as_untracked(true)
+            .disable_pathspec_match(true)
+            .exclude_submodules(!self.submodules)
+            .renames_head_to_index(self.renames)
+            .renames_index_to_workdir(self.renames);
+
+        opts
+    }
+}
@@ -1,33 +1,85 @@
-use crate::StatusEntry;
-use git2;
-use std::iter::FilterMap;
+use crate::{FileVersion, StatusEntry};
+use std::slice;
 
-pub type StatusIter<'a> =
-    FilterMap<git2::StatusIter<'a>, fn(git2::StatusEntry<'a>) -> Option<StatusEntry>>;
+pub type StatusIter<'a> = slice::Iter<'a, StatusEntry>;
 
-pub struct Statuses<'a> {
-    statuses: git2::Statuses<'a>,
+/// Changed files in a repo, read once so they can outlive it
+#[derive(Debug, Clone, Default)]
+pub struct Statuses {
+    entries: Vec<StatusEntry>,
 }
 
-impl<'a> From<git2::Statuses<'a>> for Statuses<'a> {
-    fn from(statuses: git2::Statuses<'a>) -> Self {
-        Self { statuses }
+impl From<git2::Statuses<'_>> for Statuses {
+    fn from(statuses: git2::Statuses<'_>) -> Self {
+        Self {
+            entries: statuses.iter().filter_map(entry).collect(),
+        }
     }
 }
 
-impl<'a> Statuses<'a> {
-    pub fn iter(&self) -> StatusIter<'_> {
-        let iter: StatusIter<'_> = self.statuses.iter().filter_map(|x| {
-            let path = x.path()?.into();
-            let status = x.status();
+fn entry(x: git2::StatusEntry<'_>) -> Option<StatusEntry> {
+    let status = x.status();
+
+    // libgit2 reports renames under their old path
+    let rename = x
+        .head_to_index()
+        .filter(|_| status.is_index_renamed())
+        .or_else(|| x.index_to_workdir().filter(|_| status.is_wt_renamed()));
+
+    let (path, old_path) = match rename {
+        Some(delta) => (
+            delta.new_file().path()?.into(),
+            delta.old_file().path().map(|x| x.into()),
+        ),
+        None => (x.path()?.into(), None),
+    };
+
+    let mut entry = StatusEntry::new(path, old_path, status);
+
+    if let Some(delta) = x.head_to_index() {
+        entry.head = FileVersion::from_diff_file(&delta.old_file());
+        entry.index = FileVersion::from_diff_file(&delta.new_file());
+    }
 
-            Some(StatusEntry::new(path, status))
-        });
+    if let Some(delta) = x.index_to_workdir() {
+        if entry.index.is_none() {
+            entry.index = FileVersion::from_diff_file(&delta.old_file());
+        }
 
-        iter
+        entry.worktree = FileVersion::from_diff_file(&delta.new_file());
+    }
+
+    Some(entry)
+}
+
+impl Statuses {
+    pub fn iter(&self) -> StatusIter<'_> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
     }
 
     pub fn is_empty(&self) -> bool {
-        self.statuses.is_empty()
+        self.entries.is_empty()
+    }
+}
+
+impl IntoIterator for Statuses {
+    type Item = StatusEntry;
+    type IntoIter = std::vec::IntoIter<StatusEntry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Statuses {
+    type Item = &'a StatusEntry;
+    type IntoIter = StatusIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
     }
 }
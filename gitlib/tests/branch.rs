@@ -0,0 +1,25 @@
+mod common;
+
+use crate::common::setup;
+use gitlib::{GitRepo, NetworkOptions};
+
+#[test]
+fn local_default_branch_is_not_merged_into_origin_head() {
+    let (dir, local) = setup();
+    let branch = local.head().unwrap().shorthand().unwrap().to_string();
+    let repo = GitRepo::open(dir.path().join("local")).unwrap();
+
+    repo.push_head(true, &NetworkOptions::new()).unwrap();
+    local
+        .reference_symbolic(
+            "refs/remotes/origin/HEAD",
+            &format!("refs/remotes/origin/{}", branch),
+            true,
+            "origin/HEAD",
+        )
+        .unwrap();
+    repo.create_branch("feature", "HEAD").unwrap();
+
+    assert!(!repo.branch_info(&branch).unwrap().is_merged());
+    assert!(repo.branch_info("feature").unwrap().is_merged());
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use crate::common::{set_identity, setup};
+use gitlib::{Change, Error, GitRepo, NetworkOptions, StatusOptions};
+use std::fs;
+
+fn open(dir: &tempfile::TempDir, local: &git2::Repository) -> GitRepo {
+    set_identity(local);
+
+    GitRepo::open(dir.path().join("local")).unwrap()
+}
+
+#[test]
+fn commit_stages_new_files_only_with_all() {
+    let (dir, local) = setup();
+    let repo = open(&dir, &local);
+    fs::write(dir.path().join("local/new.txt"), "new\n").unwrap();
+
+    assert!(repo.commit("tracked only", false, false).unwrap().is_none());
+
+    let outcome = repo.commit("with new files", true, false).unwrap().unwrap();
+    let head = local.head().unwrap().peel_to_commit().unwrap();
+
+    assert_eq!(outcome.id(), Some(&head.id().to_string()[..]));
+    assert_eq!(outcome.files(), 1);
+    assert!(outcome.amended().is_none());
+    assert_eq!(head.message(), Some("with new files"));
+    assert_eq!(head.author().name(), Some("git-plz"));
+    assert_eq!(head.parent_count(), 1);
+}
+
+#[test]
+fn preview_writes_no_objects() {
+    let (dir, local) = setup();
+    let repo = open(&dir, &local);
+    fs::write(dir.path().join("local/new.txt"), "preview only\n").unwrap();
+
+    let preview = repo.preview_commit(true, false).unwrap().unwrap();
+    assert_eq!(preview.files(), 1);
+
+    let blob = git2::Oid::hash_object(git2::ObjectType::Blob, b"preview only\n").unwrap();
+    assert!(!local.odb().unwrap().exists(blob));
+    assert!(local
+        .index()
+        .unwrap()
+        .get_path("new.txt".as_ref(), 0)
+        .is_none());
+}
+
+#[test]
+fn amend_is_refused_once_pushed() {
+    let (dir, local) = setup();
+    let repo = open(&dir, &local);
+    let work_dir = dir.path().join("local");
+
+    fs::write(work_dir.join("file.txt"), "one\n").unwrap();
+    repo.commit("add file.txt", true, false).unwrap();
+    let first = local.head().unwrap().target().unwrap();
+
+    fs::write(work_dir.join("file.txt"), "two\n").unwrap();
+    let preview = repo.preview_commit(false, true).unwrap().unwrap();
+    assert!(preview.id().is_none());
+    assert_eq!(preview.amended(), Some(&first.to_string()[..7]));
+
+    let outcome = repo.commit("add file.txt", false, true).unwrap().unwrap();
+    let head = local.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(outcome.amended(), Some(&first.to_string()[..7]));
+    assert_ne!(head.id(), first);
+    assert_eq!(head.parent_count(), 1);
+
+    repo.push_head(true, &NetworkOptions::new()).unwrap();
+    fs::write(work_dir.join("file.txt"), "three\n").unwrap();
+
+    assert!(matches!(
+        repo.commit("add file.txt", false, true),
+        Err(Error::AlreadyPushed)
+    ));
+
+    // The refused changes are left unstaged
+    let statuses = repo.statuses(&StatusOptions::new()).unwrap();
+    let entry = statuses.iter().next().unwrap();
+    assert_eq!(entry.index_status(), None);
+    assert_eq!(entry.worktree_status(), Some(Change::Modified));
+    assert_eq!(local.head().unwrap().target(), Some(head.id()));
+}
+
+#[test]
+fn commit_without_identity_leaves_changes_unstaged() {
+    let (dir, local) = setup();
+    let work_dir = dir.path().join("local");
+    let head = local.head().unwrap().target();
+
+    // Empty values hide any identity from the global config
+    let mut config = local.config().unwrap();
+    config.set_str("user.name", "").unwrap();
+    config.set_str("user.email", "").unwrap();
+
+    fs::write(work_dir.join("new.txt"), "new\n").unwrap();
+    let repo = GitRepo::open(&work_dir).unwrap();
+
+    assert!(matches!(
+        repo.preview_commit(true, false),
+        Err(Error::NoIdentity)
+    ));
+    assert!(matches!(
+        repo.commit("no identity", true, false),
+        Err(Error::NoIdentity)
+    ));
+
+    let statuses = repo.statuses(&StatusOptions::new()).unwrap();
+    let entry = statuses.iter().next().unwrap();
+    assert_eq!(entry.index_status(), None);
+    assert_eq!(entry.worktree_status(), Some(Change::New));
+    assert_eq!(local.head().unwrap().target(), head);
+}
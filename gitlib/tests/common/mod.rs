@@ -0,0 +1,56 @@
+// Each test binary uses a different subset of these helpers.
+#![allow(dead_code)]
+
+use std::path::Path;
+use tempfile::TempDir;
+
+pub fn commit(repo: &git2::Repository, message: &str) {
+    let signature = git2::Signature::now("git-plz", "git-plz@example.com").unwrap();
+    let tree_id = repo.index().unwrap().write_tree().unwrap();
+    let tree = repo.find_tree(tree_id).unwrap();
+    let parent = repo.head().ok().map(|x| x.peel_to_commit().unwrap());
+    let parents = parent.iter().collect::<Vec<_>>();
+
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &parents,
+    )
+    .unwrap();
+}
+
+pub fn file_url(path: &Path) -> String {
+    format!("file://{}", path.display())
+}
+
+/// A bare remote plus a working repo with one commit and `origin` pointing at it.
+pub fn setup() -> (TempDir, git2::Repository) {
+    let dir = TempDir::new().unwrap();
+    git2::Repository::init_bare(dir.path().join("remote.git")).unwrap();
+
+    let repo = git2::Repository::init(dir.path().join("local")).unwrap();
+    repo.remote("origin", &file_url(&dir.path().join("remote.git")))
+        .unwrap();
+    commit(&repo, "initial");
+
+    (dir, repo)
+}
+
+/// Give `repo` the committer identity that `commit` uses.
+pub fn set_identity(repo: &git2::Repository) {
+    let mut config = repo.config().unwrap();
+    config.set_str("user.name", "git-plz").unwrap();
+    config.set_str("user.email", "git-plz@example.com").unwrap();
+}
+
+/// Clone the bare remote made by `setup` into `name`, next to it.
+pub fn clone(dir: &TempDir, name: &str) -> git2::Repository {
+    git2::Repository::clone(
+        &file_url(&dir.path().join("remote.git")),
+        dir.path().join(name),
+    )
+    .unwrap()
+}
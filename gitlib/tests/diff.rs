@@ -0,0 +1,40 @@
+mod common;
+
+use crate::common::{commit, setup};
+use gitlib::{Change, DiffRange, GitRepo};
+use std::{fs, path::Path};
+
+#[test]
+fn diff_counts_lines_in_each_range() {
+    let (dir, local) = setup();
+    let work_dir = dir.path().join("local");
+
+    fs::write(work_dir.join("file.txt"), "one\ntwo\n").unwrap();
+    let mut index = local.index().unwrap();
+    index.add_path(Path::new("file.txt")).unwrap();
+    index.write().unwrap();
+    commit(&local, "add file.txt");
+
+    fs::write(work_dir.join("file.txt"), "one\n2\nthree\n").unwrap();
+    index.add_path(Path::new("file.txt")).unwrap();
+    index.write().unwrap();
+    fs::write(work_dir.join("file.txt"), "one\n2\nthree\nfour\n").unwrap();
+
+    let repo = GitRepo::open(&work_dir).unwrap();
+
+    let staged = repo.diff(&DiffRange::Index, false).unwrap();
+    assert_eq!(staged.files().len(), 1);
+    assert_eq!(staged.files()[0].change(), Change::Modified);
+    assert_eq!((staged.insertions(), staged.deletions()), (2, 1));
+    assert!(staged.files()[0].patch().is_none());
+
+    let unstaged = repo.diff(&DiffRange::Worktree, true).unwrap();
+    assert_eq!((unstaged.insertions(), unstaged.deletions()), (1, 0));
+    assert!(unstaged.files()[0].patch().unwrap().contains("+four"));
+
+    let range = DiffRange::Revs("HEAD~1".into(), "HEAD".into());
+    let committed = repo.diff(&range, false).unwrap();
+    assert_eq!(committed.files()[0].path(), Path::new("file.txt"));
+    assert_eq!(committed.files()[0].change(), Change::New);
+    assert_eq!(committed.insertions(), 2);
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use crate::common::{clone, commit, setup};
+use gitlib::{FetchOptions, GitRepo, NetworkOptions};
+
+/// A working repo whose branch is pushed and tracked, plus a second clone
+/// that can move the remote on.
+fn setup_tracked() -> (tempfile::TempDir, GitRepo, git2::Repository) {
+    let (dir, _) = setup();
+    let repo = GitRepo::open(dir.path().join("local")).unwrap();
+    repo.push_head(true, &NetworkOptions::new()).unwrap();
+
+    let other = clone(&dir, "other");
+
+    (dir, repo, other)
+}
+
+#[test]
+fn fetch_reports_updated_refs_then_up_to_date() {
+    let (dir, repo, other) = setup_tracked();
+    let branch = other.head().unwrap().shorthand().unwrap().to_string();
+
+    commit(&other, "theirs");
+    GitRepo::open(dir.path().join("other"))
+        .unwrap()
+        .push_head(false, &NetworkOptions::new())
+        .unwrap();
+
+    let fetched = repo.fetch("origin", &FetchOptions::new()).unwrap();
+    let updated = fetched
+        .updated_refs()
+        .iter()
+        .map(|x| x.name())
+        .collect::<Vec<_>>();
+
+    assert!(!fetched.is_up_to_date());
+    assert_eq!(updated, vec![format!("refs/remotes/origin/{}", branch)]);
+
+    let fetched = repo.fetch("origin", &FetchOptions::new()).unwrap();
+    assert!(fetched.is_up_to_date());
+}
+
+#[test]
+fn fetch_prunes_deleted_branches() {
+    let (dir, repo, _) = setup_tracked();
+    let local = git2::Repository::open(dir.path().join("local")).unwrap();
+
+    repo.create_branch("feature", "HEAD").unwrap();
+    let mut origin = local.find_remote("origin").unwrap();
+    origin
+        .push(&["refs/heads/feature:refs/heads/feature"], None)
+        .unwrap();
+    repo.fetch("origin", &FetchOptions::new()).unwrap();
+    assert!(local
+        .find_branch("origin/feature", git2::BranchType::Remote)
+        .is_ok());
+
+    let remote = git2::Repository::open_bare(dir.path().join("remote.git")).unwrap();
+    remote
+        .find_reference("refs/heads/feature")
+        .unwrap()
+        .delete()
+        .unwrap();
+
+    let fetched = repo
+        .fetch("origin", &FetchOptions::new().prune(true))
+        .unwrap();
+
+    assert!(fetched
+        .updated_refs()
+        .iter()
+        .any(|x| x.name() == "refs/remotes/origin/feature" && x.new_id().is_none()));
+    assert!(local
+        .find_branch("origin/feature", git2::BranchType::Remote)
+        .is_err());
+}
+
+#[test]
+fn last_fetched_is_set_by_fetch() {
+    let (_dir, repo, _) = setup_tracked();
+
+    assert!(repo.last_fetched().is_none());
+
+    repo.fetch("origin", &FetchOptions::new()).unwrap();
+
+    assert!(repo.last_fetched().is_some());
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use crate::common::{clone, commit, setup};
+use gitlib::{FetchOptions, GitRepo, MergeOutcome, NetworkOptions};
+
+/// Push a commit from a second clone, then fetch it into the working repo.
+fn setup_behind() -> (tempfile::TempDir, GitRepo, git2::Repository) {
+    let (dir, local) = setup();
+    let repo = GitRepo::open(dir.path().join("local")).unwrap();
+    repo.push_head(true, &NetworkOptions::new()).unwrap();
+
+    let other = clone(&dir, "other");
+    commit(&other, "theirs");
+    GitRepo::open(dir.path().join("other"))
+        .unwrap()
+        .push_head(false, &NetworkOptions::new())
+        .unwrap();
+
+    repo.fetch("origin", &FetchOptions::new()).unwrap();
+
+    (dir, repo, local)
+}
+
+fn head_id(repo: &git2::Repository) -> git2::Oid {
+    repo.head().unwrap().target().unwrap()
+}
+
+#[test]
+fn preview_then_fast_forward() {
+    let (dir, repo, local) = setup_behind();
+    let before = head_id(&local);
+
+    match repo.preview_merge_upstream(false).unwrap() {
+        MergeOutcome::FastForwarded { ref from, .. } => assert_eq!(*from, before.to_string()),
+        other => panic!("Expected a fast-forward preview, got {:?}", other),
+    }
+    assert_eq!(head_id(&local), before);
+
+    match repo.merge_upstream(false).unwrap() {
+        MergeOutcome::FastForwarded { .. } => {}
+        other => panic!("Expected a fast-forward, got {:?}", other),
+    }
+
+    let other = git2::Repository::open(dir.path().join("other")).unwrap();
+    assert_eq!(head_id(&local), head_id(&other));
+
+    match repo.preview_merge_upstream(false).unwrap() {
+        MergeOutcome::UpToDate => {}
+        other => panic!("Expected up to date, got {:?}", other),
+    }
+}
+
+#[test]
+fn preview_of_diverged_branch() {
+    let (_dir, repo, local) = setup_behind();
+    commit(&local, "ours");
+    let before = head_id(&local);
+
+    match repo.preview_merge_upstream(false).unwrap() {
+        MergeOutcome::Diverged {
+            ahead: 1,
+            behind: 1,
+        } => {}
+        other => panic!("Expected a divergence, got {:?}", other),
+    }
+
+    match repo.preview_merge_upstream(true).unwrap() {
+        MergeOutcome::Rebased { commits: 1 } => {}
+        other => panic!("Expected a rebase preview, got {:?}", other),
+    }
+
+    assert_eq!(head_id(&local), before);
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use crate::common::{clone, commit, setup};
+use gitlib::{Error, FetchOptions, GitRepo, NetworkOptions, PushOutcome};
+use std::time::Duration;
+
+#[test]
+fn push_without_upstream_is_skipped() {
+    let (dir, _) = setup();
+    let repo = GitRepo::open(dir.path().join("local")).unwrap();
+
+    assert_eq!(
+        repo.push_head(false, &NetworkOptions::new()).unwrap(),
+        PushOutcome::NoUpstream
+    );
+}
+
+#[test]
+fn push_sets_upstream_then_is_up_to_date() {
+    let (dir, local) = setup();
+    let branch = local.head().unwrap().shorthand().unwrap().to_string();
+    let upstream = format!("origin/{}", branch);
+    let repo = GitRepo::open(dir.path().join("local")).unwrap();
+
+    assert_eq!(
+        repo.push_head(true, &NetworkOptions::new()).unwrap(),
+        PushOutcome::Pushed {
+            upstream: upstream.clone()
+        }
+    );
+
+    let remote = git2::Repository::open_bare(dir.path().join("remote.git")).unwrap();
+    assert_eq!(
+        remote
+            .find_reference(&format!("refs/heads/{}", branch))
+            .unwrap()
+            .target(),
+        local.head().unwrap().target()
+    );
+
+    assert_eq!(
+        repo.push_head(false, &NetworkOptions::new()).unwrap(),
+        PushOutcome::UpToDate { upstream }
+    );
+}
+
+#[test]
+fn push_diverged_branch_is_rejected() {
+    let (dir, local) = setup();
+    let repo = GitRepo::open(dir.path().join("local")).unwrap();
+    repo.push_head(true, &NetworkOptions::new()).unwrap();
+
+    let other = clone(&dir, "other");
+    commit(&other, "theirs");
+    GitRepo::open(dir.path().join("other"))
+        .unwrap()
+        .push_head(false, &NetworkOptions::new())
+        .unwrap();
+
+    commit(&local, "ours");
+
+    match repo.push_head(false, &NetworkOptions::new()) {
+        Err(Error::NotFastForward) => {}
+        other => panic!("Expected a non-fast-forward rejection, got {:?}", other),
+    }
+}
+
+#[test]
+fn push_does_not_retry_refused_connection() {
+    let (dir, local) = setup();
+    local
+        .remote_set_url("origin", "http://127.0.0.1:9/remote.git")
+        .unwrap();
+    let repo = GitRepo::open(dir.path().join("local")).unwrap();
+    let network = NetworkOptions::new()
+        .retries(2)
+        .backoff(Duration::from_millis(1));
+
+    assert!(repo.push_head(true, &network).is_err());
+    assert_eq!(repo.network_retries(), 0);
+}
+
+#[test]
+fn preview_push_leaves_remote_untouched() {
+    let (dir, local) = setup();
+    let branch = local.head().unwrap().shorthand().unwrap().to_string();
+    let repo = GitRepo::open(dir.path().join("local")).unwrap();
+    repo.push_head(true, &NetworkOptions::new()).unwrap();
+
+    let remote = git2::Repository::open_bare(dir.path().join("remote.git")).unwrap();
+    let remote_tip = || {
+        remote
+            .find_reference(&format!("refs/heads/{}", branch))
+            .unwrap()
+            .target()
+    };
+    let pushed = remote_tip();
+
+    commit(&local, "unpushed");
+
+    assert_eq!(
+        repo.preview_push_head(false).unwrap(),
+        PushOutcome::Pushed {
+            upstream: format!("origin/{}", branch)
+        }
+    );
+    assert_eq!(remote_tip(), pushed);
+}
+
+#[test]
+fn preview_push_of_diverged_branch_is_rejected() {
+    let (dir, local) = setup();
+    let repo = GitRepo::open(dir.path().join("local")).unwrap();
+    repo.push_head(true, &NetworkOptions::new()).unwrap();
+
+    let other = clone(&dir, "other");
+    commit(&other, "theirs");
+    GitRepo::open(dir.path().join("other"))
+        .unwrap()
+        .push_head(false, &NetworkOptions::new())
+        .unwrap();
+
+    commit(&local, "ours");
+    repo.fetch("origin", &FetchOptions::new()).unwrap();
+
+    match repo.preview_push_head(false) {
+        Err(Error::NotFastForward) => {}
+        other => panic!("Expected a non-fast-forward rejection, got {:?}", other),
+    }
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use crate::common::setup;
+use gitlib::{RepoHandle, StatusOptions, Statuses};
+use std::{fs, path::Path, thread};
+
+#[test]
+fn handles_open_on_another_thread() {
+    let (dir, _) = setup();
+    let work_dir = dir.path().join("local");
+    fs::write(work_dir.join("untracked.txt"), "").unwrap();
+
+    let handle = RepoHandle::new(&work_dir).unwrap();
+    assert!(!handle.is_bare());
+
+    let statuses: Statuses = thread::spawn(move || {
+        let repo = handle.open().unwrap();
+        repo.statuses(&StatusOptions::new()).unwrap()
+    })
+    .join()
+    .unwrap();
+
+    let paths = statuses.iter().map(|x| x.path()).collect::<Vec<_>>();
+    assert_eq!(paths, vec![Path::new("untracked.txt")]);
+}
+
+#[test]
+fn handles_are_only_made_for_repos() {
+    let (dir, _) = setup();
+
+    assert!(RepoHandle::new(dir.path()).is_err());
+
+    // A stray `.git` directory isn't a repo
+    fs::create_dir_all(dir.path().join("stray/.git")).unwrap();
+    assert!(RepoHandle::new(dir.path().join("stray")).is_err());
+    assert!(RepoHandle::new(dir.path().join("remote.git"))
+        .unwrap()
+        .is_bare());
+}
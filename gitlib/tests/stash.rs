@@ -0,0 +1,43 @@
+mod common;
+
+use crate::common::{set_identity, setup};
+use gitlib::GitRepo;
+use std::fs;
+
+#[test]
+fn stash_push_list_pop_and_drop() {
+    let (dir, local) = setup();
+    let work_dir = dir.path().join("local");
+    let branch = local.head().unwrap().shorthand().unwrap().to_string();
+
+    set_identity(&local);
+
+    let mut repo = GitRepo::open(&work_dir).unwrap();
+    assert!(repo.stash_push(None, true).unwrap().is_none());
+
+    fs::write(work_dir.join("first.txt"), "first").unwrap();
+    let stash = repo.stash_push(Some("first"), true).unwrap().unwrap();
+    assert_eq!(stash.message(), "first");
+    assert_eq!(stash.branch(), Some(branch.as_str()));
+    assert!(!work_dir.join("first.txt").exists());
+
+    fs::write(work_dir.join("second.txt"), "second").unwrap();
+    repo.stash_push(Some("second"), true).unwrap().unwrap();
+
+    let messages = repo
+        .stashes()
+        .unwrap()
+        .iter()
+        .map(|x| (x.index(), x.message().to_string()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        vec![(0, "second".to_string()), (1, "first".to_string())]
+    );
+
+    repo.stash_pop(1).unwrap();
+    assert!(work_dir.join("first.txt").exists());
+
+    repo.stash_drop(0).unwrap();
+    assert!(repo.stashes().unwrap().is_empty());
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use crate::common::{commit, setup};
+use gitlib::{Change, GitRepo, NetworkOptions, Status, StatusOptions};
+use std::{fs, path::Path};
+
+#[test]
+fn head_state_counts_commits_never_pushed() {
+    let (dir, local) = setup();
+    commit(&local, "second");
+    let repo = GitRepo::open(dir.path().join("local")).unwrap();
+
+    let head = repo.head_state().unwrap();
+    assert!(head.upstream().is_none());
+    assert_eq!(head.unpushed(), 2);
+
+    repo.push_head(true, &NetworkOptions::new()).unwrap();
+    commit(&local, "third");
+
+    let head = repo.head_state().unwrap();
+    assert!(head.upstream().is_some());
+    assert_eq!((head.ahead(), head.behind(), head.unpushed()), (1, 0, 1));
+    assert!(head.operation().is_none());
+}
+
+#[test]
+fn head_state_of_detached_head() {
+    let (dir, local) = setup();
+    let id = local.head().unwrap().target().unwrap();
+    local.set_head_detached(id).unwrap();
+    let repo = GitRepo::open(dir.path().join("local")).unwrap();
+
+    let head = repo.head_state().unwrap();
+    assert!(head.branch().is_none());
+    assert_eq!(head.detached_at(), Some(&id.to_string()[..7]));
+}
+
+#[test]
+fn renames_carry_the_old_path() {
+    let (dir, local) = setup();
+    let work_dir = dir.path().join("local");
+    let contents = "a file with enough content to be detected as a rename\n".repeat(10);
+
+    fs::write(work_dir.join("old.txt"), &contents).unwrap();
+    let mut index = local.index().unwrap();
+    index.add_path(Path::new("old.txt")).unwrap();
+    index.write().unwrap();
+    commit(&local, "add old.txt");
+
+    fs::rename(work_dir.join("old.txt"), work_dir.join("new.txt")).unwrap();
+    index.remove_path(Path::new("old.txt")).unwrap();
+    index.add_path(Path::new("new.txt")).unwrap();
+    index.write().unwrap();
+
+    let repo = GitRepo::open(&work_dir).unwrap();
+
+    let statuses = repo.statuses(&StatusOptions::new()).unwrap();
+    assert_eq!(statuses.iter().count(), 2);
+
+    let statuses = repo.statuses(&StatusOptions::new().renames(true)).unwrap();
+    let entries = statuses.iter().collect::<Vec<_>>();
+
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].path(), Path::new("new.txt"));
+    assert_eq!(entries[0].old_path(), Some(Path::new("old.txt")));
+    assert!(matches!(
+        entries[0].iter().next(),
+        Some((_, Status::StagedRenamed))
+    ));
+}
+
+#[test]
+fn ignored_files_are_opt_in() {
+    let (dir, _) = setup();
+    let work_dir = dir.path().join("local");
+    fs::write(work_dir.join(".gitignore"), "*.log\n").unwrap();
+    fs::write(work_dir.join("build.log"), "").unwrap();
+
+    let repo = GitRepo::open(&work_dir).unwrap();
+    let is_ignored = |options: &StatusOptions| {
+        repo.statuses(options)
+            .unwrap()
+            .iter()
+            .any(|x| matches!(x.iter().next(), Some((_, Status::Ignored))))
+    };
+
+    assert!(!is_ignored(&StatusOptions::new()));
+    assert!(is_ignored(&StatusOptions::new().ignored(true)));
+}
+
+#[test]
+fn entries_carry_each_side_of_a_change() {
+    let (dir, local) = setup();
+    let work_dir = dir.path().join("local");
+
+    fs::write(work_dir.join("file.txt"), "committed\n").unwrap();
+    let mut index = local.index().unwrap();
+    index.add_path(Path::new("file.txt")).unwrap();
+    index.write().unwrap();
+    commit(&local, "add file.txt");
+
+    fs::write(work_dir.join("file.txt"), "staged\n").unwrap();
+    index.add_path(Path::new("file.txt")).unwrap();
+    index.write().unwrap();
+    fs::write(work_dir.join("file.txt"), "modified\n").unwrap();
+
+    let repo = GitRepo::open(&work_dir).unwrap();
+    let statuses = repo.statuses(&StatusOptions::new()).unwrap();
+    let entry = statuses.iter().next().unwrap();
+
+    assert_eq!(entry.index_status(), Some(Change::Modified));
+    assert_eq!(entry.worktree_status(), Some(Change::Modified));
+    assert_eq!((entry.index_code(), entry.worktree_code()), ('M', 'M'));
+
+    let head = entry.head().unwrap();
+    let staged = entry.index().unwrap();
+    assert_eq!(head.mode(), 0o100644);
+    assert_ne!(head.id(), staged.id());
+    assert_eq!(
+        staged.id(),
+        index
+            .get_path(Path::new("file.txt"), 0)
+            .unwrap()
+            .id
+            .to_string()
+    );
+    assert!(repo.conflict(Path::new("file.txt")).unwrap().is_none());
+}
+
+#[test]
+fn untracked_files_are_new_in_the_worktree() {
+    let (dir, _) = setup();
+    let work_dir = dir.path().join("local");
+    fs::write(work_dir.join("untracked.txt"), "").unwrap();
+
+    let repo = GitRepo::open(&work_dir).unwrap();
+    let statuses = repo.statuses(&StatusOptions::new()).unwrap();
+    let entry = statuses.iter().next().unwrap();
+
+    assert_eq!(entry.index_status(), None);
+    assert_eq!(entry.worktree_status(), Some(Change::New));
+    assert!(!entry.is_conflicted() && !entry.is_ignored());
+    assert!(entry.old_path().is_none());
+}
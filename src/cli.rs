@@ -1,6 +1,11 @@
+use crate::confirm::Confirm;
 use command::*;
-use std::{env, path::PathBuf};
-use structopt::{clap::ArgGroup, StructOpt};
+use gitlib::{DiffRange, FetchOptions, NetworkOptions, StatusOptions};
+use std::{env, path::PathBuf, time::Duration};
+use structopt::{
+    clap::{self, ArgGroup},
+    StructOpt,
+};
 
 const CMD_BRANCH: &str = "branch";
 
@@ -17,12 +22,100 @@ impl Into<PathBuf> for PathArg {
     }
 }
 
+#[derive(StructOpt, Debug)]
+struct ConfirmArg {
+    /// Apply changes without asking for confirmation
+    #[structopt(short, long)]
+    yes: bool,
+    /// Ask for confirmation before changing each repo
+    #[structopt(short, long, conflicts_with = "yes")]
+    interactive: bool,
+}
+
+impl From<ConfirmArg> for Confirm {
+    fn from(arg: ConfirmArg) -> Self {
+        if arg.yes {
+            Confirm::Never
+        } else if arg.interactive {
+            Confirm::PerRepo
+        } else {
+            Confirm::Batch
+        }
+    }
+}
+
+#[derive(StructOpt, Debug)]
+struct NetworkArg {
+    /// Most connections open to one host at a time. 0 means no limit.
+    #[structopt(long, value_name = "count", default_value = "4")]
+    connections_per_host: usize,
+    /// Times to retry timeouts and dropped connections
+    #[structopt(long, value_name = "count", default_value = "3")]
+    retries: usize,
+    /// Report what would happen from remote-tracking branches, without contacting remotes
+    #[structopt(long)]
+    offline: bool,
+}
+
+impl From<NetworkArg> for NetworkOptions {
+    fn from(arg: NetworkArg) -> Self {
+        NetworkOptions::new()
+            .connections_per_host(arg.connections_per_host)
+            .retries(arg.retries)
+    }
+}
+
+#[derive(StructOpt, Debug)]
+enum StashOption {
+    /// List every stash with its branch and age
+    List {
+        #[structopt(flatten)]
+        path: PathArg,
+    },
+    /// Stash local changes
+    Push {
+        /// Stash message
+        #[structopt(short, long)]
+        message: Option<String>,
+        /// Also stash untracked files
+        #[structopt(short = "u", long)]
+        include_untracked: bool,
+        #[structopt(flatten)]
+        path: PathArg,
+    },
+    /// Apply a stash and drop it
+    Pop {
+        /// Which stash to pop, as in stash@{index}
+        #[structopt(short = "n", long, default_value = "0")]
+        index: usize,
+        #[structopt(flatten)]
+        confirm: ConfirmArg,
+        #[structopt(flatten)]
+        path: PathArg,
+    },
+    /// Drop a stash
+    Drop {
+        /// Which stash to drop, as in stash@{index}
+        #[structopt(short = "n", long, default_value = "0")]
+        index: usize,
+        #[structopt(flatten)]
+        confirm: ConfirmArg,
+        #[structopt(flatten)]
+        path: PathArg,
+    },
+}
+
 #[derive(StructOpt, Debug)]
 #[structopt(author, about)]
 enum RunOption {
     /// Perform bulk local branch operations
-    #[structopt(group = ArgGroup::with_name("branch").required(true))]
+    #[structopt(
+        group = ArgGroup::with_name("branch").required(true),
+        group = ArgGroup::with_name("deletes").args(&["delete", "prune"]).multiple(true),
+        group = ArgGroup::with_name("remotes").args(&["delete", "rename"]).multiple(true)
+    )]
     Branch {
+        /// Find branches by name or glob pattern
         #[structopt(
             short,
             long,
@@ -30,6 +123,7 @@ enum RunOption {
             value_name = CMD_BRANCH,
         )]
         find: Option<String>,
+        /// Delete local branches by name or glob pattern
         #[structopt(
             short,
             long,
@@ -37,6 +131,61 @@ enum RunOption {
             value_name = CMD_BRANCH
         )]
         delete: Option<String>,
+        /// List local branches with their upstream and last commit
+        #[structopt(short, long, group = CMD_BRANCH)]
+        list: bool,
+        /// Find branches older than an age like 30d, 2w or 6mo, or whose upstream is gone
+        #[structopt(
+            short,
+            long,
+            group = CMD_BRANCH,
+            value_name = "age",
+            parse(try_from_str = parse_age)
+        )]
+        stale: Option<Duration>,
+        /// Delete the stale branches that are merged
+        #[structopt(long, requires = "stale")]
+        prune: bool,
+        /// Create a branch in every repo
+        #[structopt(
+            short,
+            long,
+            group = CMD_BRANCH,
+            value_name = CMD_BRANCH
+        )]
+        create: Option<String>,
+        /// Revision to create the branch from. Defaults to HEAD.
+        #[structopt(long, value_name = "rev", requires = "create")]
+        from: Option<String>,
+        /// Check out the branch after creating it
+        #[structopt(long, requires = "create")]
+        checkout: bool,
+        /// Track the remote branch given by --from, like origin/main
+        #[structopt(long, requires_all = &["create", "from"])]
+        track: bool,
+        /// Rename a branch in every repo that has it
+        #[structopt(
+            short,
+            long,
+            group = CMD_BRANCH,
+            number_of_values = 2,
+            value_names = &["old", "new"]
+        )]
+        rename: Option<Vec<String>>,
+        /// Treat the branch as a regular expression
+        #[structopt(long)]
+        regex: bool,
+        /// Include remote-tracking branches when finding
+        #[structopt(short, long, requires = "find")]
+        all: bool,
+        /// Delete branches even if they are not merged
+        #[structopt(long, requires = "deletes")]
+        force: bool,
+        /// Also delete or rename the branch on origin
+        #[structopt(long, requires = "remotes")]
+        remote: bool,
+        #[structopt(flatten)]
+        confirm: ConfirmArg,
         #[structopt(flatten)]
         path: PathArg,
     },
@@ -47,18 +196,131 @@ enum RunOption {
         #[structopt(flatten)]
         path: PathArg,
     },
+    /// Stage and commit changes across repos
+    Commit {
+        /// Commit message
+        #[structopt(short, long)]
+        message: String,
+        /// Also stage new files, not only changes to tracked files
+        #[structopt(short, long)]
+        all: bool,
+        /// Fold the changes into HEAD instead. Refused when HEAD is already pushed.
+        #[structopt(long)]
+        amend: bool,
+        #[structopt(flatten)]
+        confirm: ConfirmArg,
+        #[structopt(flatten)]
+        path: PathArg,
+    },
+    /// Diffstat or patches of changes across repos
+    Diff {
+        /// Compare the index with HEAD instead of the work tree with the index
+        #[structopt(long, conflicts_with = "from")]
+        staged: bool,
+        /// Compare this revision with HEAD, or with --to
+        #[structopt(long, value_name = "rev")]
+        from: Option<String>,
+        /// Revision to compare --from with
+        #[structopt(long, value_name = "rev", requires = "from")]
+        to: Option<String>,
+        /// Show a diffstat for each repo, the default unless --patch is given
+        #[structopt(long)]
+        stat: bool,
+        /// Show full patches
+        #[structopt(short, long)]
+        patch: bool,
+        #[structopt(flatten)]
+        path: PathArg,
+    },
+    /// Run a command in every repo, with GITPLZ_REPO_PATH and GITPLZ_BRANCH set
+    Exec {
+        /// Skip repos that haven't started once the command fails in one
+        #[structopt(long)]
+        fail_fast: bool,
+        #[structopt(flatten)]
+        path: PathArg,
+        /// Program and its arguments, after --
+        #[structopt(last = true, required = true)]
+        command: Vec<String>,
+    },
     /// Recursive fetch
     Fetch {
+        /// Fetch every remote instead of origin
+        #[structopt(short, long, conflicts_with_all = &["remote", "offline"])]
+        all: bool,
+        /// Fetch only this remote
+        #[structopt(short, long, value_name = "name", conflicts_with = "offline")]
+        remote: Option<String>,
+        /// Remove remote-tracking branches that were deleted on the remote
+        #[structopt(short, long)]
+        prune: bool,
+        /// Fetch all tags
+        #[structopt(short, long, conflicts_with = "no-tags")]
+        tags: bool,
+        /// Don't fetch any tags
+        #[structopt(long)]
+        no_tags: bool,
+        /// Hide repos where nothing changed
+        #[structopt(short, long)]
+        quiet: bool,
+        #[structopt(flatten)]
+        network: NetworkArg,
+        #[structopt(flatten)]
+        path: PathArg,
+    },
+    /// Fetch and fast-forward the checked out branch across repos
+    Pull {
+        /// Rebase local commits onto the upstream instead of skipping
+        #[structopt(short, long)]
+        rebase: bool,
+        #[structopt(flatten)]
+        network: NetworkArg,
+        #[structopt(flatten)]
+        path: PathArg,
+    },
+    /// Push the checked out branch across repos
+    Push {
+        /// Push branches without an upstream to origin and track them
+        #[structopt(short = "u", long)]
+        set_upstream: bool,
+        #[structopt(flatten)]
+        network: NetworkArg,
         #[structopt(flatten)]
         path: PathArg,
     },
     /// Recursive hard reset
     Reset {
+        #[structopt(flatten)]
+        confirm: ConfirmArg,
         #[structopt(flatten)]
         path: PathArg,
     },
+    /// List, push, pop or drop stashes across repos
+    Stash(StashOption),
     /// Recursive directory search version of git status
     Status {
+        /// Also show clean repos with commits that were never pushed
+        #[structopt(short, long)]
+        all: bool,
+        /// One line per repo: branch, ahead/behind, and counts of (S)taged, (M)odified,
+        /// (U)ntracked and (C)onflicted files
+        #[structopt(short, long)]
+        short: bool,
+        /// git status --porcelain=v2 records, after a "# repo.path" line for each repo
+        #[structopt(long, conflicts_with = "short")]
+        porcelain: bool,
+        /// Only show repos with changed files
+        #[structopt(short, long)]
+        dirty_only: bool,
+        /// Include ignored files
+        #[structopt(long)]
+        ignored: bool,
+        /// Include changed submodules
+        #[structopt(long)]
+        submodules: bool,
+        /// Detect renamed files and show where they were renamed from
+        #[structopt(long)]
+        renames: bool,
         #[structopt(flatten)]
         path: PathArg,
     },
@@ -67,42 +329,289 @@ enum RunOption {
 pub struct MappedArgs {
     command: Box<dyn Command>,
     path: PathBuf,
+    confirm: Confirm,
 }
 
 impl MappedArgs {
     fn new(run_option: RunOption) -> Self {
-        struct ArgPair(Box<dyn Command>, PathArg);
+        struct ArgPair(Box<dyn Command>, PathArg, Confirm);
 
-        let ArgPair(command, path) = match run_option {
-            RunOption::Branch { path, delete, find } => {
+        let ArgPair(command, path, confirm) = match run_option {
+            RunOption::Branch {
+                path,
+                delete,
+                find,
+                list,
+                stale,
+                prune,
+                create,
+                from,
+                checkout,
+                track,
+                rename,
+                regex,
+                all,
+                force,
+                remote,
+                confirm,
+            } => {
                 // TODO: This needs to be an enum again
                 if let Some(branch) = delete {
-                    ArgPair(Box::new(BranchDeleteCommand::new(branch)), path)
+                    ArgPair(
+                        Box::new(BranchDeleteCommand::new(
+                            branch_pattern(&branch, regex),
+                            force,
+                            remote,
+                        )),
+                        path,
+                        confirm.into(),
+                    )
                 } else if let Some(branch) = find {
-                    ArgPair(Box::new(BranchFindCommand::new(branch)), path)
+                    ArgPair(
+                        Box::new(BranchFindCommand::new(branch_pattern(&branch, regex), all)),
+                        path,
+                        Confirm::Never,
+                    )
+                } else if let Some(branch) = create {
+                    ArgPair(
+                        Box::new(BranchCreateCommand::new(
+                            branch,
+                            from.unwrap_or_else(|| "HEAD".into()),
+                            checkout,
+                            track,
+                        )),
+                        path,
+                        Confirm::Never,
+                    )
+                } else if let Some(mut names) = rename {
+                    let new_name = names.pop().expect("Missing new branch name");
+                    let branch = names.pop().expect("Missing branch name");
+
+                    ArgPair(
+                        Box::new(BranchRenameCommand::new(branch, new_name, remote)),
+                        path,
+                        Confirm::Never,
+                    )
+                } else if list {
+                    ArgPair(Box::new(BranchListCommand::new()), path, Confirm::Never)
+                } else if let Some(max_age) = stale {
+                    let confirm = if prune {
+                        confirm.into()
+                    } else {
+                        Confirm::Never
+                    };
+
+                    ArgPair(
+                        Box::new(BranchStaleCommand::new(max_age, prune, force)),
+                        path,
+                        confirm,
+                    )
                 } else {
                     panic!("Invalid branch option");
                 }
             }
             RunOption::Checkout { path, branch } => {
-                ArgPair(Box::new(CheckoutCommand::new(branch)), path)
+                ArgPair(Box::new(CheckoutCommand::new(branch)), path, Confirm::Never)
+            }
+            RunOption::Commit {
+                path,
+                message,
+                all,
+                amend,
+                confirm,
+            } => {
+                let confirm = if amend {
+                    confirm.into()
+                } else {
+                    Confirm::Never
+                };
+
+                ArgPair(
+                    Box::new(CommitCommand::new(message, all, amend)),
+                    path,
+                    confirm,
+                )
+            }
+            RunOption::Diff {
+                path,
+                staged,
+                from,
+                to,
+                stat,
+                patch,
+            } => {
+                let range = match from {
+                    Some(from) => DiffRange::Revs(from, to.unwrap_or_else(|| "HEAD".into())),
+                    None if staged => DiffRange::Index,
+                    None => DiffRange::Worktree,
+                };
+
+                ArgPair(
+                    Box::new(DiffCommand::new(range, stat || !patch, patch)),
+                    path,
+                    Confirm::Never,
+                )
+            }
+            RunOption::Exec {
+                path,
+                fail_fast,
+                command,
+            } => ArgPair(
+                Box::new(ExecCommand::new(command, fail_fast)),
+                path,
+                Confirm::Never,
+            ),
+            RunOption::Fetch {
+                path,
+                all,
+                remote,
+                prune,
+                tags,
+                no_tags,
+                quiet,
+                network,
+            } => {
+                let remotes = match remote {
+                    Some(name) => FetchRemotes::Named(name),
+                    None if all => FetchRemotes::All,
+                    None => FetchRemotes::Default,
+                };
+
+                let tags = match (tags, no_tags) {
+                    (true, _) => Some(true),
+                    (_, true) => Some(false),
+                    _ => None,
+                };
+
+                let offline = network.offline;
+                let options = FetchOptions::new()
+                    .prune(prune)
+                    .tags(tags)
+                    .network(network.into());
+
+                ArgPair(
+                    Box::new(FetchCommand::new(remotes, options, quiet, offline)),
+                    path,
+                    Confirm::Never,
+                )
+            }
+            RunOption::Pull {
+                path,
+                rebase,
+                network,
+            } => {
+                let offline = network.offline;
+
+                ArgPair(
+                    Box::new(PullCommand::new(rebase, network.into(), offline)),
+                    path,
+                    Confirm::Never,
+                )
+            }
+            RunOption::Push {
+                path,
+                set_upstream,
+                network,
+            } => {
+                let offline = network.offline;
+
+                ArgPair(
+                    Box::new(PushCommand::new(set_upstream, network.into(), offline)),
+                    path,
+                    Confirm::Never,
+                )
+            }
+            RunOption::Reset { path, confirm } => {
+                ArgPair(Box::new(ResetCommand::new()), path, confirm.into())
+            }
+            RunOption::Stash(StashOption::List { path }) => ArgPair(
+                Box::new(StashCommand::new(StashAction::List)),
+                path,
+                Confirm::Never,
+            ),
+            RunOption::Stash(StashOption::Push {
+                message,
+                include_untracked,
+                path,
+            }) => ArgPair(
+                Box::new(StashCommand::new(StashAction::Push {
+                    message,
+                    include_untracked,
+                })),
+                path,
+                Confirm::Never,
+            ),
+            RunOption::Stash(StashOption::Pop {
+                index,
+                confirm,
+                path,
+            }) => ArgPair(
+                Box::new(StashCommand::new(StashAction::Pop(index))),
+                path,
+                confirm.into(),
+            ),
+            RunOption::Stash(StashOption::Drop {
+                index,
+                confirm,
+                path,
+            }) => ArgPair(
+                Box::new(StashCommand::new(StashAction::Drop(index))),
+                path,
+                confirm.into(),
+            ),
+            RunOption::Status {
+                path,
+                all,
+                short,
+                porcelain,
+                dirty_only,
+                ignored,
+                submodules,
+                renames,
+            } => {
+                let options = StatusOptions::new()
+                    .ignored(ignored)
+                    .submodules(submodules)
+                    .renames(renames);
+
+                let format = if porcelain {
+                    StatusFormat::Porcelain
+                } else if short {
+                    StatusFormat::Short
+                } else {
+                    StatusFormat::Long
+                };
+
+                ArgPair(
+                    Box::new(StatusCommand::new(options, format, all, dirty_only)),
+                    path,
+                    Confirm::Never,
+                )
             }
-            RunOption::Fetch { path } => ArgPair(Box::new(FetchCommand::new()), path),
-            RunOption::Reset { path } => ArgPair(Box::new(ResetCommand::new()), path),
-            RunOption::Status { path } => ArgPair(Box::new(StatusCommand::new()), path),
         };
 
         Self {
             command,
             path: path.into(),
+            confirm,
         }
     }
 
-    pub fn destructure(self) -> (Box<dyn Command>, PathBuf) {
-        (self.command, self.path)
+    pub fn destructure(self) -> (Box<dyn Command>, PathBuf, Confirm) {
+        (self.command, self.path, self.confirm)
     }
 }
 
+fn branch_pattern(pattern: &str, is_regex: bool) -> BranchPattern {
+    BranchPattern::new(pattern, is_regex).unwrap_or_else(|e| {
+        clap::Error::with_description(
+            &format!("Invalid branch pattern '{}': {}", pattern, e),
+            clap::ErrorKind::InvalidValue,
+        )
+        .exit()
+    })
+}
+
 pub fn handle_args() -> MappedArgs {
     MappedArgs::new(RunOption::from_args())
 }
@@ -0,0 +1,26 @@
+use color_printer::ColorPrinter;
+use std::io::{self, BufRead, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Confirm {
+    /// Run the command without asking
+    Never,
+    /// Show the plan for every repo, then ask once
+    Batch,
+    /// Ask before running the command in each repo
+    PerRepo,
+}
+
+/// Ask a y/N question on stdin. Anything other than a yes is a no.
+pub fn prompt(printer: &mut ColorPrinter<'_>, question: &str) -> bool {
+    write!(printer, "{} [y/N] ", question).expect("write fail");
+    printer.flush().expect("flush fail");
+
+    let mut answer = String::new();
+
+    if io::stdin().lock().read_line(&mut answer).is_err() {
+        return false;
+    }
+
+    matches!(answer.trim(), "y" | "Y" | "yes" | "Yes" | "YES")
+}
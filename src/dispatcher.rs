@@ -1,10 +1,13 @@
 use color_printer::ColorPrinter;
-use command::{Command, WorkResult, WorkType};
+use command::{Command, RepoErrorResult, WorkResult, WorkType};
+use gitlib::RepoHandle;
 use std::{collections::BTreeMap, sync::mpsc::Receiver};
 use threadpool::ThreadPool;
 
 const THREAD_SIGNAL: &str = "Could not signal main thread with WorkType::Work";
 
+pub type PlannedWork = (RepoHandle, Box<dyn WorkResult>);
+
 pub struct Dispatcher<'a> {
     queue: BTreeMap<usize, Option<Box<dyn WorkResult>>>,
     next_index: usize,
@@ -24,13 +27,24 @@ impl<'a> Dispatcher<'a> {
         }
     }
 
+    pub fn printer(&mut self) -> &mut ColorPrinter<'a> {
+        &mut self.printer
+    }
+
     pub fn run(&mut self, rx: &Receiver<WorkType>) {
+        self.next_index = 0;
+
         while let Ok(result) = rx.recv() {
             match result {
                 WorkType::Repo { index, repo, tx } => {
                     let worker = self.command.box_clone();
                     self.pool.execute(move || {
-                        let result = match worker.process(repo) {
+                        let result = match repo.open() {
+                            Ok(x) => worker.process(x),
+                            Err(e) => Some(open_error(&repo, e)),
+                        };
+
+                        let result = match result {
                             Some(r) => WorkType::result(index, r),
                             None => WorkType::empty(index),
                         };
@@ -56,6 +70,7 @@ impl<'a> Dispatcher<'a> {
                     // If there are adjacent items in the queue, process them.
                     self.process_queue();
                 }
+                WorkType::Plan { .. } => panic!("Received a plan while running a command"),
             }
         }
 
@@ -69,6 +84,44 @@ impl<'a> Dispatcher<'a> {
         }
     }
 
+    /// Ask the command what it would do in every repo, without changing anything.
+    /// Plans are returned in discovery order; repos with nothing to do are dropped,
+    /// and repos that fail to open are printed as they are found.
+    pub fn plan(&mut self, rx: &Receiver<WorkType>) -> Vec<PlannedWork> {
+        let mut plans = BTreeMap::new();
+
+        while let Ok(result) = rx.recv() {
+            match result {
+                WorkType::Repo { index, repo, tx } => {
+                    let worker = self.command.box_clone();
+                    self.pool.execute(move || {
+                        let result = match repo.open() {
+                            Ok(x) => match worker.plan(&x) {
+                                Some(r) => WorkType::plan(index, repo, r),
+                                None => WorkType::empty(index),
+                            },
+                            // Nothing can be planned, so report it straight away
+                            Err(e) => WorkType::result(index, open_error(&repo, e)),
+                        };
+
+                        tx.send(result).expect(THREAD_SIGNAL)
+                    })
+                }
+                WorkType::Plan {
+                    index,
+                    repo,
+                    result,
+                } => {
+                    plans.insert(index, (repo, result));
+                }
+                WorkType::WorkEmpty { .. } => {}
+                WorkType::Work { result, .. } => result.print(&mut self.printer),
+            }
+        }
+
+        plans.into_values().collect()
+    }
+
     fn process_queue(&mut self) {
         self.next_index += 1;
 
@@ -81,3 +134,7 @@ impl<'a> Dispatcher<'a> {
         }
     }
 }
+
+fn open_error(repo: &RepoHandle, error: gitlib::Error) -> Box<dyn WorkResult> {
+    Box::new(RepoErrorResult::new(repo.path().into(), error))
+}
@@ -1,36 +1,110 @@
 mod cli;
+mod confirm;
 mod dispatcher;
 
-use crate::dispatcher::Dispatcher;
+use crate::{
+    confirm::Confirm,
+    dispatcher::{Dispatcher, PlannedWork},
+};
 use color_printer::ColorPrinter;
 use command::WorkType;
+use gitlib::RepoHandle;
 use std::{
-    path::PathBuf,
+    io::Write,
     sync::mpsc::{channel, Receiver},
 };
 use threadpool::ThreadPool;
 use util::RepoIter;
 
 fn main() {
-    let (command, working_path) = cli::handle_args().destructure();
+    let (command, working_path, confirm) = cli::handle_args().destructure();
 
     let is_terminal = atty::is(atty::Stream::Stdout);
     let stream = color_printer::StandardStream::stdout(color_printer::ColorChoice::Auto);
     let printer = ColorPrinter::new(is_terminal, &stream);
 
     let pool = threadpool::Builder::new().build();
-    let rx = start_repo_iter(working_path, &pool);
+    let rx = start_repo_iter(RepoIter::new(working_path), &pool);
 
     let mut dispatcher = Dispatcher::new(&pool, printer, command);
+
+    if confirm == Confirm::Never {
+        dispatcher.run(&rx);
+        return;
+    }
+
+    let plans = dispatcher.plan(&rx);
+
+    if plans.is_empty() {
+        return;
+    }
+
+    let approved = approve_plans(&mut dispatcher, plans, confirm);
+    let rx = start_repo_iter(approved.into_iter(), &pool);
+
     dispatcher.run(&rx);
 }
 
-fn start_repo_iter(working_dir: PathBuf, pool: &ThreadPool) -> Receiver<WorkType> {
+fn approve_plans(
+    dispatcher: &mut Dispatcher<'_>,
+    plans: Vec<PlannedWork>,
+    confirm: Confirm,
+) -> Vec<RepoHandle> {
+    let printer = dispatcher.printer();
+
+    if !atty::is(atty::Stream::Stdin) {
+        for (_, plan) in &plans {
+            plan.print(printer);
+        }
+
+        writeln!(
+            printer,
+            "stdin is not a terminal, nothing was changed. Re-run with --yes to apply."
+        )
+        .expect("write fail");
+
+        return Vec::new();
+    }
+
+    match confirm {
+        Confirm::Never => plans.into_iter().map(|(repo, _)| repo).collect(),
+        Confirm::Batch => {
+            for (_, plan) in &plans {
+                plan.print(printer);
+            }
+
+            let question = format!("Apply to {} repo(s)?", plans.len());
+
+            if confirm::prompt(printer, &question) {
+                plans.into_iter().map(|(repo, _)| repo).collect()
+            } else {
+                Vec::new()
+            }
+        }
+        Confirm::PerRepo => plans
+            .into_iter()
+            .filter_map(|(repo, plan)| {
+                plan.print(printer);
+
+                if confirm::prompt(printer, "Apply?") {
+                    Some(repo)
+                } else {
+                    None
+                }
+            })
+            .collect(),
+    }
+}
+
+fn start_repo_iter<I>(repos: I, pool: &ThreadPool) -> Receiver<WorkType>
+where
+    I: Iterator<Item = RepoHandle> + Send + 'static,
+{
     let (tx, rx) = channel();
     let tx_send = tx.clone();
 
     pool.execute(move || {
-        for (index, repo) in RepoIter::new(working_dir).enumerate() {
+        for (index, repo) in repos.enumerate() {
             tx.send(WorkType::repo(index, repo, tx_send.clone()))
                 .expect("Could not signal main thread with WorkType::Repo");
         }
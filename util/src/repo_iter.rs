@@ -1,4 +1,4 @@
-use gitlib::GitRepo;
+use gitlib::RepoHandle;
 use std::path::PathBuf;
 
 use crate::repo_iter_state::RepoIterState;
@@ -19,7 +19,7 @@ impl RepoIter {
 }
 
 impl Iterator for RepoIter {
-    type Item = GitRepo;
+    type Item = RepoHandle;
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
@@ -40,7 +40,7 @@ impl Iterator for RepoIter {
                 Some(Ok(entry)) => entry,
             };
 
-            if let Ok(repo) = GitRepo::open(entry.path()) {
+            if let Ok(repo) = RepoHandle::new(entry.path()) {
                 break Some(repo);
             }
 